@@ -0,0 +1,223 @@
+//! An owned, high-level representation of IDTF content.
+//!
+//! Where the **SectionReader** maps the layout of each section directly onto the bytes from which
+//! it is read, the types in this module own their data and unify the four point formats into a
+//! single **Point** type with resolved colors. This is useful for applications that would rather
+//! work with a whole file at once than stream it section by section.
+
+use crate::{layout, Error, Palettes, SectionReader, SubsectionReaderKind};
+use std::{io::Read, path::Path};
+
+/// A single point within a **Frame**.
+///
+/// Unifies the four IDTF point formats. Colors are always resolved to RGB, either directly from
+/// the true color formats or via the palette in effect for the indexed color formats.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point {
+    /// left negative, right positive.
+    pub x: i16,
+    /// down negative, up positive.
+    pub y: i16,
+    /// far negative, near positive. `None` for points read from the 2D formats.
+    pub z: Option<i16>,
+    /// The resolved color of the point. Always black for blanked points.
+    pub color: layout::Color,
+    /// Whether or not the laser is off (blank) for this point.
+    pub blanking: bool,
+}
+
+/// A single frame of points.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Frame {
+    /// The name of the frame.
+    pub name: String,
+    /// The name of the company who created the frame.
+    pub company: String,
+    /// The frame number within its group or sequence.
+    pub number: u16,
+    /// The total number of frames within the group or sequence.
+    pub total: u16,
+    /// The projector number that this frame is to be displayed on.
+    pub projector: u8,
+    /// The points of the frame in the order in which they should be drawn.
+    pub points: Vec<Point>,
+}
+
+/// A color palette used to resolve the colors of indexed color frames.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Palette {
+    /// The name of the palette.
+    pub name: String,
+    /// The name of the company who created the palette.
+    pub company: String,
+    /// The palette number.
+    pub number: u16,
+    /// The projector number that this palette applies to.
+    pub projector: u8,
+    /// The ordered list of colors.
+    pub colors: Vec<layout::Color>,
+}
+
+/// All frames and palettes of an IDTF file in the order in which they were read.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Animation {
+    pub frames: Vec<Frame>,
+    pub palettes: Vec<Palette>,
+}
+
+impl Point {
+    /// The coordinates of the point normalised to the range `-1.0..=1.0`.
+    ///
+    /// The `z` coordinate is `0.0` for 2D points.
+    pub fn position_f32(&self) -> [f32; 3] {
+        let f = |c: i16| c as f32 / i16::MAX as f32;
+        [f(self.x), f(self.y), self.z.map(f).unwrap_or(0.0)]
+    }
+}
+
+impl Animation {
+    /// Read all remaining sections from the given **SectionReader** into an **Animation**.
+    ///
+    /// Reading stops at the end of the stream or at the end of file header, whichever comes
+    /// first. Indexed colors are resolved via the most recently read palette for the frame's
+    /// projector, falling back to the **DEFAULT_PALETTE** if no palette has been read yet.
+    pub fn read_from<R>(reader: &mut SectionReader<R>) -> Result<Self, Error>
+    where
+        R: Read,
+    {
+        let mut animation = Animation::default();
+        let mut palettes = Palettes::new();
+        while let Some(section) = reader.read_next()? {
+            let header = section.header;
+            if header.num_records.get() == 0 {
+                break;
+            }
+            let name = name_string(&header.data_name);
+            let company = name_string(&header.company_name);
+            let number = header.data_number.get();
+            let total = header.color_or_total_frames.get();
+            let projector = header.projector_number;
+            let mut points = Vec::with_capacity(header.num_records.get() as usize);
+            let palette = palettes.get(projector);
+            match section.reader {
+                SubsectionReaderKind::Coords3dIndexedColor(mut r) => {
+                    while let Some(p) = r.read_next()? {
+                        let color = crate::resolve_color(palette, p.color_index, p.status);
+                        points.push(point_3d(&p.coords, p.status, color));
+                    }
+                }
+                SubsectionReaderKind::Coords2dIndexedColor(mut r) => {
+                    while let Some(p) = r.read_next()? {
+                        let color = crate::resolve_color(palette, p.color_index, p.status);
+                        points.push(point_2d(&p.coords, p.status, color));
+                    }
+                }
+                SubsectionReaderKind::ColorPalette(mut r) => {
+                    let mut colors = Vec::with_capacity(header.num_records.get() as usize);
+                    while let Some(p) = r.read_next()? {
+                        colors.push(p.color);
+                    }
+                    palettes.set(projector, colors.clone());
+                    animation.palettes.push(Palette {
+                        name,
+                        company,
+                        number,
+                        projector,
+                        colors,
+                    });
+                    continue;
+                }
+                SubsectionReaderKind::Coords3dTrueColor(mut r) => {
+                    while let Some(p) = r.read_next()? {
+                        points.push(point_3d(&p.coords, p.status, p.color));
+                    }
+                }
+                SubsectionReaderKind::Coords2dTrueColor(mut r) => {
+                    while let Some(p) = r.read_next()? {
+                        points.push(point_2d(&p.coords, p.status, p.color));
+                    }
+                }
+            }
+            animation.frames.push(Frame {
+                name,
+                company,
+                number,
+                total,
+                projector,
+                points,
+            });
+        }
+        Ok(animation)
+    }
+
+    /// Group the frames into sequences for playback.
+    ///
+    /// Frames are grouped per projector in the order in which they were read. A frame begins a
+    /// new sequence for its projector if its number is 0 or if its total number of frames differs
+    /// from that of the current sequence.
+    pub fn sequences(&self) -> Vec<Vec<&Frame>> {
+        let mut sequences: Vec<Vec<&Frame>> = vec![];
+        // The index of the current sequence for each projector.
+        let mut current: Vec<(u8, usize)> = vec![];
+        for frame in &self.frames {
+            let entry = current.iter_mut().find(|(p, _)| *p == frame.projector);
+            match entry {
+                Some((_, i)) if frame.number != 0 && sequences[*i][0].total == frame.total => {
+                    sequences[*i].push(frame);
+                }
+                Some((_, i)) => {
+                    *i = sequences.len();
+                    sequences.push(vec![frame]);
+                }
+                None => {
+                    current.push((frame.projector, sequences.len()));
+                    sequences.push(vec![frame]);
+                }
+            }
+        }
+        sequences
+    }
+}
+
+/// Read the whole IDTF file at the given path into an **Animation**.
+pub fn read_animation<P>(path: P) -> Result<Animation, Error>
+where
+    P: AsRef<Path>,
+{
+    let mut reader = crate::open(path)?;
+    Animation::read_from(&mut reader)
+}
+
+fn point_3d(coords: &layout::Coords3d, status: layout::Status, color: layout::Color) -> Point {
+    let z = Some(coords.z.get());
+    point(coords.x.get(), coords.y.get(), z, status, color)
+}
+
+fn point_2d(coords: &layout::Coords2d, status: layout::Status, color: layout::Color) -> Point {
+    point(coords.x.get(), coords.y.get(), None, status, color)
+}
+
+fn point(x: i16, y: i16, z: Option<i16>, status: layout::Status, color: layout::Color) -> Point {
+    let blanking = status.is_blanking();
+    let color = if blanking {
+        layout::Color::BLACK
+    } else {
+        color
+    };
+    Point {
+        x,
+        y,
+        z,
+        color,
+        blanking,
+    }
+}
+
+fn name_string(name: &layout::Name) -> String {
+    let len = name.0.iter().position(|&b| b == 0).unwrap_or(name.0.len());
+    String::from_utf8_lossy(&name.0[..len]).into_owned()
+}
@@ -0,0 +1,432 @@
+//! Items related to converting between the IDTF point formats.
+//!
+//! Indexed color points may be converted to true color by resolving their color index via a
+//! palette. True color points may be converted to indexed color for hardware that only accepts
+//! formats 0 and 1 by mapping each color onto the nearest color within a palette. The
+//! [**generate_palette**](./fn.generate_palette.html) function may be used to produce a palette
+//! suited to a specific set of frames.
+//!
+//! 3D points may be converted to 2D for hardware that only accepts formats 1 and 5 via either an
+//! orthographic or a perspective [**Projection**](./enum.Projection.html). 2D points may be
+//! promoted to 3D, lying on the plane where z is 0.
+
+use crate::layout;
+use std::collections::HashMap;
+
+type I16 = zerocopy::byteorder::I16<layout::Endianness>;
+
+/// Indexed color points that may be converted to an equivalent true color point.
+pub trait IndexedColorPoint: layout::PointLayout {
+    /// The equivalent true color layout.
+    type TrueColor: TrueColorPoint;
+    /// Resolve the color index via the given palette, producing the equivalent true color point.
+    fn to_true_color(&self, palette: &[layout::Color]) -> Self::TrueColor;
+}
+
+/// True color points that may be converted to an equivalent indexed color point.
+pub trait TrueColorPoint: layout::PointLayout {
+    /// The equivalent indexed color layout.
+    type IndexedColor: IndexedColorPoint;
+    /// Map the color onto the nearest color within the given palette, producing the equivalent
+    /// indexed color point.
+    fn to_indexed_color(&self, palette: &[layout::Color]) -> Self::IndexedColor;
+}
+
+/// 3D points that may be projected onto an equivalent 2D point.
+pub trait Coords3dPoint: layout::PointLayout {
+    /// The equivalent 2D layout.
+    type Coords2d: Coords2dPoint;
+    /// Project the point onto the plane where z is 0, producing the equivalent 2D point.
+    fn to_2d(&self, projection: Projection) -> Self::Coords2d;
+}
+
+/// 2D points that may be promoted to an equivalent 3D point.
+pub trait Coords2dPoint: layout::PointLayout {
+    /// The equivalent 3D layout.
+    type Coords3d: Coords3dPoint;
+    /// Produce the equivalent 3D point with a z coordinate of 0.
+    fn to_3d(&self) -> Self::Coords3d;
+}
+
+/// Describes how 3D coordinates are projected onto 2D coordinates.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Projection {
+    /// Drop the z coordinate.
+    Orthographic,
+    /// View the points from a camera on the z axis, looking towards the origin from positive z.
+    ///
+    /// Points on the plane where z is 0 are unchanged. Points nearer to the camera are scaled
+    /// away from the origin and points further away are scaled towards it. Resulting coordinates
+    /// are clamped to the range of `i16`.
+    ///
+    /// Points at or behind the camera cannot be seen. Their x and y coordinates are moved to the
+    /// edge of the range of `i16` in the direction of their sign, remaining 0 where they are 0,
+    /// and points converted via **Coords3dPoint::to_2d** are blanked so that they are not drawn.
+    ///
+    /// Prefer constructing via **Projection::perspective**, which verifies the camera distance.
+    /// With a camera distance that is not positive, all points are considered to be behind the
+    /// camera.
+    Perspective {
+        /// The distance from the camera to the origin in coordinate units. Must be positive.
+        camera_distance: f32,
+    },
+}
+
+/// Convert a frame of indexed color points to true color via the given palette.
+///
+/// E.g. converts the records of a format 0 section to the records of a format 4 section.
+pub fn to_true_color<P>(points: &[P], palette: &[layout::Color]) -> Vec<P::TrueColor>
+where
+    P: IndexedColorPoint,
+{
+    points.iter().map(|p| p.to_true_color(palette)).collect()
+}
+
+/// Convert a frame of true color points to indexed color via the given palette.
+///
+/// E.g. converts the records of a format 4 section to the records of a format 0 section. Each
+/// color is mapped onto the nearest color within the palette via
+/// [**nearest_color_index**](./fn.nearest_color_index.html).
+pub fn to_indexed_color<P>(points: &[P], palette: &[layout::Color]) -> Vec<P::IndexedColor>
+where
+    P: TrueColorPoint,
+{
+    points.iter().map(|p| p.to_indexed_color(palette)).collect()
+}
+
+/// Convert a frame of 3D points to 2D via the given projection.
+///
+/// E.g. converts the records of a format 4 section to the records of a format 5 section.
+pub fn to_2d<P>(points: &[P], projection: Projection) -> Vec<P::Coords2d>
+where
+    P: Coords3dPoint,
+{
+    points.iter().map(|p| p.to_2d(projection)).collect()
+}
+
+/// Convert a frame of 2D points to 3D with a z coordinate of 0.
+///
+/// E.g. converts the records of a format 5 section to the records of a format 4 section.
+pub fn to_3d<P>(points: &[P]) -> Vec<P::Coords3d>
+where
+    P: Coords2dPoint,
+{
+    points.iter().map(|p| p.to_3d()).collect()
+}
+
+/// The index of the color within the palette that is nearest to the given color.
+///
+/// Distance is measured as the squared euclidean distance between RGB values. The first of
+/// multiple equally near colors is chosen. Returns `0` for an empty palette. Only the first 256
+/// colors of the palette are considered.
+pub fn nearest_color_index(palette: &[layout::Color], color: layout::Color) -> u8 {
+    palette
+        .iter()
+        .take(256)
+        .enumerate()
+        .min_by_key(|&(_, &c)| distance_squared(c, color))
+        .map(|(i, _)| i as u8)
+        .unwrap_or(0)
+}
+
+/// Generate a palette of at most `max_len` colors that best represents the given colors.
+///
+/// If there are no more than `max_len` distinct colors, the palette contains exactly those
+/// colors. Otherwise the colors are reduced via median cut quantization. `max_len` is clamped to
+/// the range 2 to 256 permitted for color palette sections, and palettes with fewer than 2 colors
+/// are padded with black.
+///
+/// Typically the colors of all unblanked points within a set of frames are provided.
+pub fn generate_palette<I>(colors: I, max_len: usize) -> Vec<layout::Color>
+where
+    I: IntoIterator<Item = layout::Color>,
+{
+    let max_len = max_len.clamp(2, 256);
+
+    // Count the occurrences of each distinct color.
+    let mut counts = HashMap::new();
+    for color in colors {
+        *counts.entry(color).or_insert(0u64) += 1;
+    }
+    let mut colors: Vec<(layout::Color, u64)> = counts.into_iter().collect();
+    colors.sort_by_key(|&(c, _)| (c.red, c.green, c.blue));
+
+    let mut palette: Vec<layout::Color> = if colors.len() <= max_len {
+        colors.into_iter().map(|(c, _)| c).collect()
+    } else {
+        median_cut(colors, max_len)
+            .iter()
+            .map(|b| mean_color(b))
+            .collect()
+    };
+    while palette.len() < 2 {
+        palette.push(layout::Color::BLACK);
+    }
+    palette
+}
+
+/// Produce the records of a color palette section from the given colors.
+pub fn palette_records(palette: &[layout::Color]) -> Vec<layout::ColorPalette> {
+    palette
+        .iter()
+        .map(|&color| layout::ColorPalette { color })
+        .collect()
+}
+
+impl layout::Coords3dTrueColor {
+    /// Map the color onto the nearest color within the given palette, producing the equivalent
+    /// indexed color point.
+    ///
+    /// See [**nearest_color_index**](../convert/fn.nearest_color_index.html).
+    pub fn to_indexed_color(&self, palette: &[layout::Color]) -> layout::Coords3dIndexedColor {
+        layout::Coords3dIndexedColor {
+            coords: self.coords,
+            status: self.status,
+            color_index: nearest_color_index(palette, self.color),
+        }
+    }
+}
+
+impl layout::Coords2dTrueColor {
+    /// Map the color onto the nearest color within the given palette, producing the equivalent
+    /// indexed color point.
+    ///
+    /// See [**nearest_color_index**](../convert/fn.nearest_color_index.html).
+    pub fn to_indexed_color(&self, palette: &[layout::Color]) -> layout::Coords2dIndexedColor {
+        layout::Coords2dIndexedColor {
+            coords: self.coords,
+            status: self.status,
+            color_index: nearest_color_index(palette, self.color),
+        }
+    }
+}
+
+impl Projection {
+    /// A perspective projection with the camera at the given distance from the origin.
+    ///
+    /// Returns `None` if the distance is not positive or not finite.
+    pub fn perspective(camera_distance: f32) -> Option<Self> {
+        if camera_distance > 0.0 && camera_distance.is_finite() {
+            Some(Projection::Perspective { camera_distance })
+        } else {
+            None
+        }
+    }
+}
+
+impl layout::Coords3d {
+    /// Project the coordinates onto the plane where z is 0.
+    ///
+    /// See [**Projection**](../convert/enum.Projection.html) for the treatment of points at or
+    /// behind the camera, which includes all points if the camera distance is not positive.
+    pub fn project(&self, projection: Projection) -> layout::Coords2d {
+        let (x, y) = match projection {
+            Projection::Orthographic => (self.x, self.y),
+            Projection::Perspective { camera_distance } => {
+                let depth = camera_distance - self.z.get() as f32;
+                let behind = self.is_behind_camera(projection);
+                let scale = |c: I16| {
+                    let c = c.get();
+                    if behind {
+                        // Avoid multiplying by infinity, which produces NaN for 0.
+                        c.signum().saturating_mul(i16::MAX)
+                    } else {
+                        crate::saturate(c as f32 * camera_distance / depth)
+                    }
+                };
+                (I16::new(scale(self.x)), I16::new(scale(self.y)))
+            }
+        };
+        layout::Coords2d { x, y }
+    }
+
+    /// Whether the coordinates lie at or behind the camera of the given projection.
+    ///
+    /// Always `false` for an orthographic projection and always `true` for a perspective
+    /// projection whose camera distance is not positive.
+    pub fn is_behind_camera(&self, projection: Projection) -> bool {
+        match projection {
+            Projection::Orthographic => false,
+            Projection::Perspective { camera_distance } => {
+                camera_distance.is_nan()
+                    || camera_distance <= 0.0
+                    || self.z.get() as f32 >= camera_distance
+            }
+        }
+    }
+}
+
+impl layout::Coords2d {
+    /// Promote the coordinates to 3D with a z coordinate of 0.
+    pub fn to_3d(&self) -> layout::Coords3d {
+        layout::Coords3d {
+            x: self.x,
+            y: self.y,
+            z: I16::new(0),
+        }
+    }
+}
+
+impl Coords3dPoint for layout::Coords3dIndexedColor {
+    type Coords2d = layout::Coords2dIndexedColor;
+    fn to_2d(&self, projection: Projection) -> Self::Coords2d {
+        layout::Coords2dIndexedColor {
+            coords: self.coords.project(projection),
+            status: projected_status(&self.coords, self.status, projection),
+            color_index: self.color_index,
+        }
+    }
+}
+
+impl Coords3dPoint for layout::Coords3dTrueColor {
+    type Coords2d = layout::Coords2dTrueColor;
+    fn to_2d(&self, projection: Projection) -> Self::Coords2d {
+        layout::Coords2dTrueColor {
+            coords: self.coords.project(projection),
+            status: projected_status(&self.coords, self.status, projection),
+            color: self.color,
+        }
+    }
+}
+
+impl Coords2dPoint for layout::Coords2dIndexedColor {
+    type Coords3d = layout::Coords3dIndexedColor;
+    fn to_3d(&self) -> Self::Coords3d {
+        layout::Coords3dIndexedColor {
+            coords: self.coords.to_3d(),
+            status: self.status,
+            color_index: self.color_index,
+        }
+    }
+}
+
+impl Coords2dPoint for layout::Coords2dTrueColor {
+    type Coords3d = layout::Coords3dTrueColor;
+    fn to_3d(&self) -> Self::Coords3d {
+        layout::Coords3dTrueColor {
+            coords: self.coords.to_3d(),
+            status: self.status,
+            color: self.color,
+        }
+    }
+}
+
+impl IndexedColorPoint for layout::Coords3dIndexedColor {
+    type TrueColor = layout::Coords3dTrueColor;
+    fn to_true_color(&self, palette: &[layout::Color]) -> Self::TrueColor {
+        layout::Coords3dIndexedColor::to_true_color(self, palette)
+    }
+}
+
+impl IndexedColorPoint for layout::Coords2dIndexedColor {
+    type TrueColor = layout::Coords2dTrueColor;
+    fn to_true_color(&self, palette: &[layout::Color]) -> Self::TrueColor {
+        layout::Coords2dIndexedColor::to_true_color(self, palette)
+    }
+}
+
+impl TrueColorPoint for layout::Coords3dTrueColor {
+    type IndexedColor = layout::Coords3dIndexedColor;
+    fn to_indexed_color(&self, palette: &[layout::Color]) -> Self::IndexedColor {
+        layout::Coords3dTrueColor::to_indexed_color(self, palette)
+    }
+}
+
+impl TrueColorPoint for layout::Coords2dTrueColor {
+    type IndexedColor = layout::Coords2dIndexedColor;
+    fn to_indexed_color(&self, palette: &[layout::Color]) -> Self::IndexedColor {
+        layout::Coords2dTrueColor::to_indexed_color(self, palette)
+    }
+}
+
+fn distance_squared(a: layout::Color, b: layout::Color) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(a.red, b.red) + d(a.green, b.green) + d(a.blue, b.blue)
+}
+
+// Split the weighted colors into at most `max_len` boxes.
+//
+// The box with the widest channel range is repeatedly split at the weighted median of that channel.
+fn median_cut(colors: Vec<(layout::Color, u64)>, max_len: usize) -> Vec<Vec<(layout::Color, u64)>> {
+    let mut boxes = vec![colors];
+    while boxes.len() < max_len {
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.len() > 1)
+            .map(|(i, b)| (i, widest_channel(b)))
+            .max_by_key(|&(_, (_, range))| range);
+        let (i, channel) = match widest {
+            None => break,
+            Some((i, (channel, _))) => (i, channel),
+        };
+        let mut b = boxes.swap_remove(i);
+        b.sort_by_key(|&(c, _)| channel_value(c, channel));
+        let half = b.iter().map(|&(_, n)| n).sum::<u64>() / 2;
+        let mut acc = 0;
+        let mut split = b.len() - 1;
+        for (j, &(_, n)) in b.iter().enumerate() {
+            acc += n;
+            if acc > half {
+                split = j;
+                break;
+            }
+        }
+        let split = split.clamp(1, b.len() - 1);
+        let rest = b.split_off(split);
+        boxes.push(b);
+        boxes.push(rest);
+    }
+    boxes
+}
+
+// The index of the channel with the widest range of values along with the range.
+fn widest_channel(colors: &[(layout::Color, u64)]) -> (usize, u8) {
+    (0..3)
+        .map(|channel| {
+            let values = colors.iter().map(|&(c, _)| channel_value(c, channel));
+            let min = values.clone().min().unwrap_or(0);
+            let max = values.max().unwrap_or(0);
+            (channel, max - min)
+        })
+        .max_by_key(|&(_, range)| range)
+        .expect("there are three channels")
+}
+
+fn channel_value(color: layout::Color, channel: usize) -> u8 {
+    match channel {
+        0 => color.red,
+        1 => color.green,
+        _ => color.blue,
+    }
+}
+
+// The mean of the weighted colors.
+fn mean_color(colors: &[(layout::Color, u64)]) -> layout::Color {
+    let total: u64 = colors.iter().map(|&(_, n)| n).sum();
+    let mean = |channel| {
+        let sum: u64 = colors
+            .iter()
+            .map(|&(c, n)| channel_value(c, channel) as u64 * n)
+            .sum();
+        ((sum + total / 2) / total) as u8
+    };
+    layout::Color {
+        red: mean(0),
+        green: mean(1),
+        blue: mean(2),
+    }
+}
+
+// Blank points that cannot be seen by the projection's camera.
+fn projected_status(
+    coords: &layout::Coords3d,
+    status: layout::Status,
+    projection: Projection,
+) -> layout::Status {
+    if coords.is_behind_camera(projection) {
+        status | layout::Status::BLANKING
+    } else {
+        status
+    }
+}
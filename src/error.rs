@@ -0,0 +1,206 @@
+//! Items related to the errors that may occur while reading or writing IDTF.
+
+use crate::layout;
+use std::{fmt, io};
+
+/// The location within an IDTF stream at which an error occurred.
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Position {
+    /// The index of the section in which the error occurred, counting from 0.
+    pub section: usize,
+    /// The byte offset from the start of the stream.
+    pub offset: u64,
+}
+
+/// The errors that may occur while reading or writing IDTF.
+#[derive(Debug)]
+pub enum Error {
+    /// The header did not begin with the ASCII letters "ILDA".
+    BadMagic { position: Position, found: [u8; 4] },
+    /// The header specified a format code that is not defined by the specification.
+    UnknownFormat { position: Position, format: u8 },
+    /// The header specified a different format to that of the records being written.
+    UnexpectedFormat {
+        position: Position,
+        expected: layout::Format,
+        found: layout::Format,
+    },
+    /// The stream ended part way through a section header.
+    TruncatedHeader { position: Position, got: usize },
+    /// The section ended before all records specified by its header were read or written.
+    TruncatedSection {
+        position: Position,
+        expected: u16,
+        got: u16,
+    },
+    /// More records were written than specified by the section's header.
+    ExcessRecords { position: Position, expected: u16 },
+    /// A section header with a `num_records` of `0` was written, which would mark the end of the
+    /// file.
+    EmptySection { position: Position },
+    /// The header specified more records than the reader permits.
+    RecordLimitExceeded {
+        position: Position,
+        num_records: u16,
+        max: u16,
+    },
+    /// One of the reserved header fields was not zeroed.
+    NonZeroReserved { position: Position },
+    /// A color palette header specified a number of records outside the range 2 to 256.
+    InvalidPaletteSize { position: Position, len: u16 },
+    /// An error occurred within the underlying reader or writer.
+    Io(io::Error),
+}
+
+/// Non-fatal problems encountered while reading IDTF.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Warning {
+    /// Bytes were skipped in search of the next valid header.
+    SkippedBytes { position: Position, len: u64 },
+    /// The stream ended part way through a section header, which was ignored.
+    TruncatedHeader { position: Position, got: usize },
+}
+
+impl Error {
+    /// The location at which the error occurred.
+    ///
+    /// Returns `None` for I/O errors.
+    pub fn position(&self) -> Option<Position> {
+        match *self {
+            Error::BadMagic { position, .. }
+            | Error::UnknownFormat { position, .. }
+            | Error::UnexpectedFormat { position, .. }
+            | Error::TruncatedHeader { position, .. }
+            | Error::TruncatedSection { position, .. }
+            | Error::ExcessRecords { position, .. }
+            | Error::EmptySection { position }
+            | Error::RecordLimitExceeded { position, .. }
+            | Error::NonZeroReserved { position }
+            | Error::InvalidPaletteSize { position, .. } => Some(position),
+            Error::Io(_) => None,
+        }
+    }
+}
+
+impl Warning {
+    /// The location at which the problem was encountered.
+    pub fn position(&self) -> Position {
+        match *self {
+            Warning::SkippedBytes { position, .. } => position,
+            Warning::TruncatedHeader { position, .. } => position,
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "section {} (byte offset {})", self.section, self.offset)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::BadMagic { position, found } => write!(
+                f,
+                "could not verify `Header` due to invalid ILDA ascii {:?} at {}",
+                found, position,
+            ),
+            Error::UnknownFormat { position, format } => {
+                write!(f, "unknown format code {} at {}", format, position)
+            }
+            Error::UnexpectedFormat {
+                position,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected format code {} but `Header` specifies {} at {}",
+                expected.0, found.0, position,
+            ),
+            Error::TruncatedHeader { position, got } => write!(
+                f,
+                "stream ended after {} of 32 `Header` bytes at {}",
+                got, position,
+            ),
+            Error::TruncatedSection {
+                position,
+                expected,
+                got,
+            } => write!(
+                f,
+                "section truncated after {} of {} records at {}",
+                got, expected, position,
+            ),
+            Error::ExcessRecords { position, expected } => write!(
+                f,
+                "more than the {} records specified by `Header` at {}",
+                expected, position,
+            ),
+            Error::EmptySection { position } => write!(
+                f,
+                "section `Header` specifies no records, marking the end of file, at {}",
+                position,
+            ),
+            Error::RecordLimitExceeded {
+                position,
+                num_records,
+                max,
+            } => write!(
+                f,
+                "`Header` specifies {} records exceeding the limit of {} at {}",
+                num_records, max, position,
+            ),
+            Error::NonZeroReserved { position } => {
+                write!(f, "reserved `Header` field not zeroed at {}", position)
+            }
+            Error::InvalidPaletteSize { position, len } => write!(
+                f,
+                "color palette of {} records is outside the range 2 to 256 at {}",
+                len, position,
+            ),
+            Error::Io(ref err) => fmt::Display::fmt(err, f),
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Warning::SkippedBytes { position, len } => write!(
+                f,
+                "skipped {} bytes in search of a valid `Header` at {}",
+                len, position,
+            ),
+            Warning::TruncatedHeader { position, got } => write!(
+                f,
+                "ignored a truncated `Header` of {} bytes at {}",
+                got, position,
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Error::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Io(err) => err,
+            err => io::Error::new(io::ErrorKind::InvalidData, err),
+        }
+    }
+}
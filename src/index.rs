@@ -0,0 +1,164 @@
+//! Items related to indexing the sections of a seekable IDTF stream for random access.
+
+use crate::{layout, record_size, Error, Position, SectionReader};
+use std::{
+    io::{Read, Seek, SeekFrom},
+    mem,
+};
+
+/// An index of the sections within a seekable IDTF stream.
+///
+/// Building the index only reads section headers, seeking over the records in between. This
+/// allows for quickly locating a section or frame within long animations without decoding every
+/// preceding section.
+///
+/// Offsets assume that the IDTF stream begins at the start of the reader.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SectionIndex {
+    entries: Vec<IndexEntry>,
+}
+
+/// The location and header of a single indexed section.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct IndexEntry {
+    /// The index of the section and the byte offset of its header from the start of the stream.
+    pub position: Position,
+    /// The section header.
+    pub header: layout::Header,
+}
+
+impl SectionIndex {
+    /// Index all sections of the given reader.
+    ///
+    /// Scanning begins at the start of the reader and stops at the end of file header or the end
+    /// of the stream. The reader is left at an unspecified position.
+    pub fn build<R>(reader: &mut R) -> Result<Self, Error>
+    where
+        R: Read + Seek,
+    {
+        let mut entries = vec![];
+        let mut offset = reader.seek(SeekFrom::Start(0))?;
+        let mut buffer = [0u8; mem::size_of::<layout::Header>()];
+        loop {
+            match crate::read_fully(reader, &mut buffer)? {
+                n if n < buffer.len() => break,
+                _ => (),
+            }
+            let header: &layout::Header = zerocopy::LayoutVerified::new(&buffer[..])
+                .map(zerocopy::LayoutVerified::into_ref)
+                .expect("buffer size must match the layout of `Header`");
+            let position = Position {
+                section: entries.len(),
+                offset,
+            };
+            if header.ilda != layout::Header::ILDA {
+                return Err(Error::BadMagic {
+                    position,
+                    found: header.ilda,
+                });
+            }
+            let size = match record_size(header.format) {
+                Some(size) => size,
+                None => {
+                    return Err(Error::UnknownFormat {
+                        position,
+                        format: header.format.0,
+                    })
+                }
+            };
+            let len = header.num_records.get();
+            if len == 0 {
+                break;
+            }
+            entries.push(IndexEntry {
+                position,
+                header: *header,
+            });
+            let records_len = len as i64 * size as i64;
+            offset = reader.seek(SeekFrom::Current(records_len))?;
+        }
+        Ok(SectionIndex { entries })
+    }
+
+    /// All indexed sections in the order in which they appear within the stream.
+    pub fn entries(&self) -> &[IndexEntry] {
+        &self.entries
+    }
+
+    /// The number of indexed sections.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether or not the index contains no sections.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The entry for the section at the given index.
+    pub fn get(&self, section: usize) -> Option<&IndexEntry> {
+        self.entries.get(section)
+    }
+
+    /// Find the index of the frame section with the given projector and frame number.
+    ///
+    /// Color palette sections are ignored.
+    pub fn find_frame(&self, projector: u8, number: u16) -> Option<usize> {
+        self.entries.iter().position(|entry| {
+            let h = &entry.header;
+            h.format != layout::Format::COLOR_PALETTE
+                && h.projector_number == projector
+                && h.data_number.get() == number
+        })
+    }
+}
+
+impl<R> SectionReader<R>
+where
+    R: Read + Seek,
+{
+    /// Index all sections of the stream.
+    ///
+    /// The reader is returned to its current position once indexing is complete.
+    ///
+    /// See **SectionIndex::build**.
+    pub fn index(&mut self) -> Result<SectionIndex, Error> {
+        let index = SectionIndex::build(&mut self.reader);
+        self.reader.seek(SeekFrom::Start(self.position.offset))?;
+        index
+    }
+
+    /// Seek to the section at the given index so that it is produced by the next call to
+    /// **read_next**.
+    ///
+    /// Returns `false` and leaves the reader untouched if the index contains no such section.
+    ///
+    /// Note that palettes tracked via **palette_tracking** are not updated for the sections that
+    /// are skipped over.
+    pub fn seek_to_section(&mut self, index: &SectionIndex, section: usize) -> Result<bool, Error> {
+        let entry = match index.get(section) {
+            None => return Ok(false),
+            Some(entry) => entry,
+        };
+        self.reader.seek(SeekFrom::Start(entry.position.offset))?;
+        self.position = entry.position;
+        self.end_of_file = None;
+        Ok(true)
+    }
+
+    /// Seek to the frame with the given projector and frame number so that it is produced by the
+    /// next call to **read_next**.
+    ///
+    /// Returns `false` and leaves the reader untouched if the index contains no such frame.
+    pub fn seek_to_frame(
+        &mut self,
+        index: &SectionIndex,
+        projector: u8,
+        number: u16,
+    ) -> Result<bool, Error> {
+        match index.find_frame(projector, number) {
+            None => Ok(false),
+            Some(section) => self.seek_to_section(index, section),
+        }
+    }
+}
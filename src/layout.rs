@@ -35,10 +35,16 @@ pub struct Name(pub [u8; 8]);
 /// Describes the layout of a section of IDTF.
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, AsBytes, FromBytes, Unaligned)]
 #[repr(C)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Header {
     /// The ASCII letters ILDA, identifying an ILDA format header.
+    #[cfg_attr(feature = "serde", serde(skip, default = "crate::serialize::ilda"))]
     pub ilda: [u8; 4],
     /// Reserved for future use. Must be zeroed.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "crate::serialize::is_zero")
+    )]
     pub reserved: [u8; 3],
     /// One of the format codes defined in the Format Codes section.
     pub format: Format,
@@ -52,39 +58,53 @@ pub struct Header {
     /// unsigned integer (0 – 65535). If the number of records is 0, then this is to be taken as
     /// the end of file header and nomore data will follow this header. For color palettes, the
     /// number of records SHALL be between 2 and 256.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::be_u16"))]
     pub num_records: U16,
     /// Frame or color palette number. If the frame is part of a group such as an animation
     /// sequence, thisrepresents the frame number. Counting begins with frame 0. Range is 0 –
     /// 65534.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::be_u16"))]
     pub data_number: U16,
     /// Total frames in this group or sequence. Range is 1 – 65535.
     ///
     /// For colorpalettes this SHALL be 0.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::be_u16"))]
     pub color_or_total_frames: U16,
     /// The projector number that this frame is to be displayed on. Range is 0 – 255. For single
     /// projector files this SHOULD be set 0.
     pub projector_number: u8,
     /// Reserved for future use. Must be zeroed.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "crate::serialize::is_zero")
+    )]
     pub reserved2: u8,
 }
 
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, AsBytes, FromBytes, Unaligned)]
 #[repr(C)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Coords3d {
     /// left negative, right positive.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::be_i16"))]
     pub x: I16,
     /// down negative, up positive.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::be_i16"))]
     pub y: I16,
     /// far negative, near positive.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::be_i16"))]
     pub z: I16,
 }
 
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, AsBytes, FromBytes, Unaligned)]
 #[repr(C)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Coords2d {
     /// left negative, right positive.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::be_i16"))]
     pub x: I16,
     /// down negative, up positive.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::be_i16"))]
     pub y: I16,
 }
 
@@ -107,6 +127,7 @@ bitflags! {
 
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, AsBytes, FromBytes, Unaligned)]
 #[repr(C)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Color {
     pub red: u8,
     pub green: u8,
@@ -115,6 +136,7 @@ pub struct Color {
 
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, AsBytes, FromBytes, Unaligned)]
 #[repr(C)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Coords3dIndexedColor {
     pub coords: Coords3d,
     pub status: Status,
@@ -123,6 +145,7 @@ pub struct Coords3dIndexedColor {
 
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, AsBytes, FromBytes, Unaligned)]
 #[repr(C)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Coords2dIndexedColor {
     pub coords: Coords2d,
     pub status: Status,
@@ -131,12 +154,14 @@ pub struct Coords2dIndexedColor {
 
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, AsBytes, FromBytes, Unaligned)]
 #[repr(C)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ColorPalette {
     pub color: Color,
 }
 
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, AsBytes, FromBytes, Unaligned)]
 #[repr(C)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Coords3dTrueColor {
     pub coords: Coords3d,
     pub status: Status,
@@ -145,12 +170,33 @@ pub struct Coords3dTrueColor {
 
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, AsBytes, FromBytes, Unaligned)]
 #[repr(C)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Coords2dTrueColor {
     pub coords: Coords2d,
     pub status: Status,
     pub color: Color,
 }
 
+/// The interface shared by the four point layouts.
+///
+/// Allows for working with points without regard for whether they are 2D or 3D, or whether their
+/// colors are indexed or true color.
+pub trait PointLayout {
+    /// The X coordinate.
+    fn x(&self) -> i16;
+    /// The Y coordinate.
+    fn y(&self) -> i16;
+    /// The Z coordinate, or `None` for 2D points.
+    fn z(&self) -> Option<i16>;
+    /// The status of the point.
+    fn status(&self) -> Status;
+    /// The color of the point, resolving indexed colors via the given palette.
+    ///
+    /// The palette is ignored for true color points. As with
+    /// [**resolve_color**](../fn.resolve_color.html), blanked points are black.
+    fn color(&self, palette: &[Color]) -> Color;
+}
+
 impl Format {
     pub const COORDS_3D_INDEXED_COLOR: Self = Self(0);
     pub const COORDS_2D_INDEXED_COLOR: Self = Self(1);
@@ -160,6 +206,16 @@ impl Format {
 }
 
 impl Name {
+    /// Create a **Name** from the given ASCII string.
+    ///
+    /// The string is truncated to 8 bytes. Shorter strings are padded with binary zeros.
+    pub fn new(s: &str) -> Self {
+        let mut bytes = [0u8; 8];
+        let len = s.len().min(bytes.len());
+        bytes[..len].copy_from_slice(&s.as_bytes()[..len]);
+        Name(bytes)
+    }
+
     /// Read the ascii bytes as a UTF8 str.
     pub fn as_str(&self) -> Result<&str, std::str::Utf8Error> {
         let len = self.0.iter().position(|&b| b == 0).unwrap_or(self.0.len());
@@ -167,8 +223,34 @@ impl Name {
     }
 }
 
+impl Color {
+    pub const BLACK: Self = Color {
+        red: 0,
+        green: 0,
+        blue: 0,
+    };
+}
+
 impl Header {
     pub const ILDA: [u8; 4] = [0x49, 0x4c, 0x44, 0x41];
+
+    /// A header for a section of the given format containing `num_records` records.
+    ///
+    /// All other fields are zeroed.
+    pub fn new(format: Format, num_records: u16) -> Self {
+        Header {
+            ilda: Self::ILDA,
+            reserved: [0; 3],
+            format,
+            data_name: Name([0; 8]),
+            company_name: Name([0; 8]),
+            num_records: U16::new(num_records),
+            data_number: U16::new(0),
+            color_or_total_frames: U16::new(0),
+            projector_number: 0,
+            reserved2: 0,
+        }
+    }
 }
 
 impl Status {
@@ -181,6 +263,87 @@ impl Status {
     }
 }
 
+impl PointLayout for Coords3dIndexedColor {
+    fn x(&self) -> i16 {
+        self.coords.x.get()
+    }
+    fn y(&self) -> i16 {
+        self.coords.y.get()
+    }
+    fn z(&self) -> Option<i16> {
+        Some(self.coords.z.get())
+    }
+    fn status(&self) -> Status {
+        self.status
+    }
+    fn color(&self, palette: &[Color]) -> Color {
+        crate::resolve_color(palette, self.color_index, self.status)
+    }
+}
+
+impl PointLayout for Coords2dIndexedColor {
+    fn x(&self) -> i16 {
+        self.coords.x.get()
+    }
+    fn y(&self) -> i16 {
+        self.coords.y.get()
+    }
+    fn z(&self) -> Option<i16> {
+        None
+    }
+    fn status(&self) -> Status {
+        self.status
+    }
+    fn color(&self, palette: &[Color]) -> Color {
+        crate::resolve_color(palette, self.color_index, self.status)
+    }
+}
+
+impl PointLayout for Coords3dTrueColor {
+    fn x(&self) -> i16 {
+        self.coords.x.get()
+    }
+    fn y(&self) -> i16 {
+        self.coords.y.get()
+    }
+    fn z(&self) -> Option<i16> {
+        Some(self.coords.z.get())
+    }
+    fn status(&self) -> Status {
+        self.status
+    }
+    fn color(&self, _palette: &[Color]) -> Color {
+        true_color(self.color, self.status)
+    }
+}
+
+impl PointLayout for Coords2dTrueColor {
+    fn x(&self) -> i16 {
+        self.coords.x.get()
+    }
+    fn y(&self) -> i16 {
+        self.coords.y.get()
+    }
+    fn z(&self) -> Option<i16> {
+        None
+    }
+    fn status(&self) -> Status {
+        self.status
+    }
+    fn color(&self, _palette: &[Color]) -> Color {
+        true_color(self.color, self.status)
+    }
+}
+
+// The blanking bit takes precedence over the RGB values.
+fn true_color(color: Color, status: Status) -> Color {
+    if status.is_blanking() {
+        Color::BLACK
+    } else {
+        color
+    }
+}
+
 impl std::fmt::Debug for Name {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self.as_str() {
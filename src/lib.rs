@@ -76,6 +76,108 @@
 //! preceding `Section`, this is not always the case. The ILDA IDTF specification recommends a
 //! default palette. This palette is provided via the [**DEFAULT_PALETTE**][7] constant.
 //!
+//! When palette tracking is enabled, the **SectionReader** remembers the most recently read
+//! palette for each projector and provides it via the section's `palette` field. Indexed color
+//! points may then be resolved to true color as they are read.
+//!
+//! ```rust,no_run
+//! # let mut reader = ilda_idtf::open("/foo/bar").unwrap().palette_tracking(true);
+//! # while let Ok(Some(section)) = reader.read_next() {
+//! let palette = section.palette;
+//! if let ilda_idtf::SubsectionReaderKind::Coords3dIndexedColor(mut r) = section.reader {
+//!     while let Some(point) = r.read_next_resolved(palette).unwrap() {
+//!         // ...
+//!     }
+//! }
+//! # }
+//! ```
+//!
+//! The [**SectionWriter**][8] type can be used to write IDTF sections to any type implementing
+//! `std::io::Write`. Each section begins with a **Header** followed by exactly `num_records`
+//! subsections of the layout described by the header's **Format**. Finishing the writer emits the
+//! end of file header.
+//!
+//! ```rust,no_run
+//! # let points: Vec<ilda_idtf::layout::Coords2dTrueColor> = vec![];
+//! use ilda_idtf::layout::{Format, Header};
+//! let mut writer = ilda_idtf::SectionWriter::new(vec![]);
+//! let header = Header::new(Format::COORDS_2D_TRUE_COLOR, points.len() as u16);
+//! writer.write_section(&header, &points).unwrap();
+//! let bytes = writer.finish().unwrap();
+//! ```
+//!
+//! The [**SliceSectionReader**][11] type reads sections directly from a slice of bytes without
+//! copying. Each section's records are provided as a single slice. With the `memmap` feature
+//! enabled, the unsafe [**open_mmap**][12] function may be used to memory-map a file for this
+//! purpose, provided the file is not modified while it is mapped.
+//!
+//! ```rust,no_run
+//! # let bytes = &[0u8][..];
+//! let mut reader = ilda_idtf::SliceSectionReader::new(bytes);
+//! while let Ok(Some(section)) = reader.read_next() {
+//!     if let ilda_idtf::SliceRecords::Coords3dTrueColor(points) = section.records {
+//!         // ...
+//!     }
+//! }
+//! ```
+//!
+//! For applications that would rather work with a whole file at once, the [**read_animation**][9]
+//! function reads all frames and palettes of a file into an owned [**Animation**][10]. The four
+//! point formats are unified into a single **Point** type with resolved colors.
+//!
+//! ```rust,no_run
+//! let animation = ilda_idtf::read_animation("/foo/bar").unwrap();
+//! for frame in &animation.frames {
+//!     for point in &frame.points {
+//!         // ...
+//!     }
+//! }
+//! ```
+//!
+//! With the `serde` feature enabled, the layout types and the **Animation** types implement
+//! `Serialize` and `Deserialize` using human-friendly forms, e.g. for dumping a file to JSON.
+//!
+//! The [**validate::validate_path**][13] function checks a file for conformance with the
+//! specification, reporting every violation found along with its position rather than stopping at
+//! the first.
+//!
+//! ```rust,no_run
+//! let report = ilda_idtf::validate::validate_path("/foo/bar").unwrap();
+//! for violation in &report.violations {
+//!     println!("{}", violation);
+//! }
+//! ```
+//!
+//! The [**render::RenderOptions**][14] type rasterizes frames on the CPU as anti-aliased lines with
+//! additive blending, optionally visualizing beam glow and point dwell. With the `png` feature
+//! enabled, the resulting images may be saved as PNG. Whole sequences of frames, as grouped by
+//! **Animation::sequences**, may be exported as animated PNG or, with the `gif` feature, animated
+//! GIF.
+//!
+//! ```rust,no_run
+//! # fn render(frame: &ilda_idtf::Frame) {
+//! let image = ilda_idtf::render::RenderOptions::new()
+//!     .size(256, 256)
+//!     .glow(Some(4.0))
+//!     .frame(frame)
+//!     .unwrap();
+//! # }
+//! ```
+//!
+//! The [**optimize**][15] module prepares frames for display by laser scanners. The
+//! **optimize_paths** function reorders the lit paths of a frame to minimize blanked travel while
+//! **DwellOptions** inserts blank dwell around lit paths and dwell at corners. The **resample**
+//! function limits the distance between consecutive points, while **decimate** removes redundant
+//! points along straight lines.
+//!
+//! ```rust,no_run
+//! # let points: Vec<ilda_idtf::layout::Coords3dTrueColor> = vec![];
+//! let points = ilda_idtf::optimize::optimize_paths(&points, true).unwrap();
+//! let points = ilda_idtf::optimize::decimate(&points, 1.0);
+//! let points = ilda_idtf::optimize::resample(&points, 512.0, 2048.0).unwrap();
+//! let points = ilda_idtf::optimize::DwellOptions::new().apply(&points).unwrap();
+//! ```
+//!
 //! [1]: struct.SectionReader.html
 //! [2]: fn.open.html
 //! [3]: struct.SectionReader.html#method.read_next
@@ -83,6 +185,14 @@
 //! [5]: layout/struct.Header.html
 //! [6]: layout/struct.Format.html
 //! [7]: constant.DEFAULT_PALETTE.html
+//! [8]: struct.SectionWriter.html
+//! [9]: animation/fn.read_animation.html
+//! [10]: animation/struct.Animation.html
+//! [11]: struct.SliceSectionReader.html
+//! [12]: fn.open_mmap.html
+//! [13]: validate/fn.validate_path.html
+//! [14]: render/struct.RenderOptions.html
+//! [15]: optimize/index.html
 
 #[macro_use]
 extern crate bitflags;
@@ -93,7 +203,34 @@ use std::{
     path::Path,
 };
 
+pub mod animation;
+pub mod convert;
+mod error;
+mod index;
 pub mod layout;
+pub mod optimize;
+mod palette;
+pub mod render;
+#[cfg(feature = "serde")]
+mod serialize;
+mod slice;
+pub mod svg;
+pub mod text;
+pub mod transform;
+pub mod validate;
+mod writer;
+
+pub use animation::{read_animation, Animation, Frame, Palette};
+pub use error::{Error, Position, Warning};
+pub use index::{IndexEntry, SectionIndex};
+pub use palette::{resolve_color, Palettes};
+#[cfg(feature = "memmap")]
+pub use slice::open_mmap;
+pub use slice::{SliceRecords, SliceSection, SliceSectionReader};
+pub use writer::{
+    ColorPaletteWriter, Coords2dIndexedColorWriter, Coords2dTrueColorWriter,
+    Coords3dIndexedColorWriter, Coords3dTrueColorWriter, SectionWriter, SubsectionWriter,
+};
 
 /// A helper trait for producing and working with precisely sized buffers for IDTF layout.
 pub trait LayoutBuffer: zerocopy::FromBytes {
@@ -103,12 +240,26 @@ pub trait LayoutBuffer: zerocopy::FromBytes {
     fn slice_mut(buffer: &mut Self::Buffer) -> &mut [u8];
 }
 
+/// Associates a subsection layout with the **Format** of the sections in which it appears.
+pub trait SubsectionLayout: LayoutBuffer + zerocopy::AsBytes {
+    /// The format code of sections containing this subsection layout.
+    const FORMAT: layout::Format;
+}
+
 /// Reads a sequence of frames from the ILDA IDTF spec from a stream of bytes.
 ///
 /// Reads `Section`s.
 pub struct SectionReader<R> {
     reader: R,
     buffer: [u8; mem::size_of::<layout::Header>()],
+    palettes: Option<Palettes>,
+    position: Position,
+    strict: bool,
+    stop_at_end_of_file: bool,
+    max_records: u16,
+    end_of_file: Option<layout::Header>,
+    recover: bool,
+    warnings: Vec<Warning>,
 }
 
 /// Contains a verified **Header** and a reader for the section contents.
@@ -117,6 +268,12 @@ where
     R: Read,
 {
     pub header: &'a layout::Header,
+    /// The palette in effect for the section's projector.
+    ///
+    /// When palette tracking is enabled this is the most recently read palette for the projector,
+    /// including the palette of this section if it is a **ColorPalette** section. Otherwise this
+    /// is always the **DEFAULT_PALETTE**.
+    pub palette: &'a [layout::Color],
     pub reader: SubsectionReaderKind<R>,
 }
 
@@ -128,7 +285,10 @@ where
 {
     reader: R,
     len: u16,
+    total: u16,
+    position: Position,
     buffer: T::Buffer,
+    prefetched: Option<io::Cursor<Vec<u8>>>,
     subsection_layout: std::marker::PhantomData<T>,
 }
 
@@ -157,63 +317,297 @@ where
     /// Read ILDA IDTF sections from the given reader.
     pub fn new(reader: R) -> Self {
         let buffer = [0u8; mem::size_of::<layout::Header>()];
-        SectionReader { reader, buffer }
+        let palettes = None;
+        let position = Position::default();
+        SectionReader {
+            reader,
+            buffer,
+            palettes,
+            position,
+            strict: false,
+            stop_at_end_of_file: true,
+            max_records: u16::MAX,
+            end_of_file: None,
+            recover: false,
+            warnings: vec![],
+        }
+    }
+
+    /// Enable or disable strict mode.
+    ///
+    /// In strict mode the reader:
+    ///
+    /// - Returns a **TruncatedHeader** error if the stream ends part way through a header rather
+    ///   than treating it as the end of the stream and recording a **Warning**.
+    /// - Returns a **NonZeroReserved** error for headers whose reserved fields are not zeroed.
+    /// - Returns an **InvalidPaletteSize** error for palettes with fewer than 2 or more than 256
+    ///   colors.
+    ///
+    /// Strict mode is disabled by default.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Whether or not to stop reading at the end of file header.
+    ///
+    /// As per the specification, a header with a `num_records` of `0` marks the end of the file.
+    /// When enabled, the end of file header is not yielded as a **Section**. Instead,
+    /// **read_next** returns `None` and the header becomes accessible via **end_of_file**. No
+    /// further bytes are read from the stream.
+    ///
+    /// When disabled, end of file headers are yielded as empty sections and reading continues
+    /// until the end of the stream.
+    ///
+    /// Enabled by default.
+    pub fn stop_at_end_of_file(mut self, stop: bool) -> Self {
+        self.stop_at_end_of_file = stop;
+        self
+    }
+
+    /// The maximum number of records permitted within a single section.
+    ///
+    /// Sections whose headers specify more records produce a **RecordLimitExceeded** error. This
+    /// is useful for bounding the work performed on untrusted input.
+    ///
+    /// Defaults to `u16::MAX`, i.e. no limit.
+    pub fn max_records(mut self, max: u16) -> Self {
+        self.max_records = max;
+        self
+    }
+
+    /// The end of file header, if one has been read.
+    ///
+    /// Allows for distinguishing between a stream that was explicitly terminated by an end of file
+    /// header and a stream that simply ended. Always `None` if **stop_at_end_of_file** is
+    /// disabled.
+    pub fn end_of_file(&self) -> Option<&layout::Header> {
+        self.end_of_file.as_ref()
+    }
+
+    /// Enable or disable recovery mode.
+    ///
+    /// Corrupt files, e.g. those with an incorrect `num_records`, cause the reader to misalign with
+    /// the following headers. In recovery mode, rather than returning a **BadMagic** or
+    /// **UnknownFormat** error, the reader scans forward for the next ASCII "ILDA" and continues
+    /// reading from there. Each range of skipped bytes is recorded as a **Warning**.
+    ///
+    /// Note that the "ILDA" sequence may also occur within record data, in which case a section
+    /// may be read from an incorrect offset.
+    ///
+    /// Recovery mode is disabled by default.
+    pub fn recover(mut self, recover: bool) -> Self {
+        self.recover = recover;
+        self
+    }
+
+    /// The warnings produced so far while reading.
+    ///
+    /// Warnings are produced for the bytes skipped in recovery mode and for a truncated header at
+    /// the end of the stream outside of strict mode.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Enable or disable palette tracking.
+    ///
+    /// When enabled, the reader remembers the most recently read **ColorPalette** section for
+    /// each projector number. The palette in effect for each section's projector is provided via
+    /// the **Section**'s `palette` field, falling back to the **DEFAULT_PALETTE** for projectors
+    /// that have not yet received a palette.
+    ///
+    /// Palette tracking is disabled by default.
+    pub fn palette_tracking(mut self, enabled: bool) -> Self {
+        self.palettes = if enabled { Some(Palettes::new()) } else { None };
+        self
+    }
+
+    /// The tracked palettes, if palette tracking is enabled.
+    pub fn palettes(&self) -> Option<&Palettes> {
+        self.palettes.as_ref()
+    }
+
+    /// The position of the next section header.
+    ///
+    /// Assumes that the previously read section, if any, is read to completion.
+    pub fn position(&self) -> Position {
+        self.position
     }
 
     /// Begin reading the next **Section**.
     ///
     /// A successfully read **Section** contains a verified **Header** and a reader for the section
     /// contents.
-    pub fn read_next(&mut self) -> io::Result<Option<Section<&mut R>>> {
+    pub fn read_next(&mut self) -> Result<Option<Section<'_, &mut R>>, Error> {
         let SectionReader {
             ref mut buffer,
             ref mut reader,
+            ref mut palettes,
+            ref mut position,
+            strict,
+            stop_at_end_of_file,
+            max_records,
+            ref mut end_of_file,
+            recover,
+            ref mut warnings,
         } = *self;
 
-        // Buffer the header bytes.
-        if let Err(err) = reader.read_exact(buffer) {
-            if let io::ErrorKind::UnexpectedEof = err.kind() {
+        if end_of_file.is_some() {
+            return Ok(None);
+        }
+
+        // Buffer the header bytes, distinguishing the end of the stream from a truncated header.
+        match read_fully(reader, buffer)? {
+            0 => return Ok(None),
+            n if n < buffer.len() => {
+                if strict {
+                    return Err(Error::TruncatedHeader {
+                        position: *position,
+                        got: n,
+                    });
+                }
+                warnings.push(Warning::TruncatedHeader {
+                    position: *position,
+                    got: n,
+                });
                 return Ok(None);
             }
+            _ => (),
         }
 
-        // Verify the header layout.
-        let header: &layout::Header = zerocopy::LayoutVerified::new(&buffer[..])
-            .map(zerocopy::LayoutVerified::into_ref)
-            .ok_or_else(|| {
-                let err_msg = "could not verify the layout of `Header`";
-                io::Error::new(io::ErrorKind::InvalidData, err_msg)
-            })?;
-
-        // Validate header by ascii "ILDA".
-        if header.ilda != layout::Header::ILDA {
-            let err_msg = "could not verify `Header` due to invalid ILDA ascii";
-            return Err(io::Error::new(io::ErrorKind::InvalidData, err_msg));
+        // Validate header by ascii "ILDA" and a known format, resynchronising if recovering.
+        loop {
+            let header = header_ref(buffer);
+            let err = if header.ilda != layout::Header::ILDA {
+                Error::BadMagic {
+                    position: *position,
+                    found: header.ilda,
+                }
+            } else if record_size(header.format).is_none() {
+                Error::UnknownFormat {
+                    position: *position,
+                    format: header.format.0,
+                }
+            } else {
+                break;
+            };
+            if !recover {
+                return Err(err);
+            }
+            let (len, found) = resync(reader, buffer)?;
+            warnings.push(Warning::SkippedBytes {
+                position: *position,
+                len,
+            });
+            position.offset += len;
+            if !found {
+                return Ok(None);
+            }
         }
 
-        // Determine the format.
+        // Verify the header layout.
+        let header = header_ref(buffer);
+        let section_position = *position;
+
+        // Determine the size of the section so that the position of the next may be known.
         let len = header.num_records.get();
+        let record_size = record_size(header.format).expect("format verified above");
+
+        // Stop at the end of file header.
+        if len == 0 && stop_at_end_of_file {
+            *end_of_file = Some(*header);
+            return Ok(None);
+        }
+
+        if len > max_records {
+            return Err(Error::RecordLimitExceeded {
+                position: section_position,
+                num_records: len,
+                max: max_records,
+            });
+        }
+
+        if strict {
+            if header.reserved != [0; 3] || header.reserved2 != 0 {
+                return Err(Error::NonZeroReserved {
+                    position: section_position,
+                });
+            }
+            let is_palette = header.format == layout::Format::COLOR_PALETTE;
+            if is_palette && len != 0 && !(2..=256).contains(&len) {
+                return Err(Error::InvalidPaletteSize {
+                    position: section_position,
+                    len,
+                });
+            }
+        }
+
+        position.section += 1;
+        position.offset += (mem::size_of::<layout::Header>() + len as usize * record_size) as u64;
+
+        // Track the palette so that it may be used to resolve the colors of following sections.
+        let mut prefetched = None;
+        if let Some(ref mut palettes) = *palettes {
+            if header.format == layout::Format::COLOR_PALETTE {
+                let bytes_len = len as usize * record_size;
+                let mut bytes = Vec::with_capacity(bytes_len);
+                reader
+                    .by_ref()
+                    .take(bytes_len as u64)
+                    .read_to_end(&mut bytes)?;
+                if bytes.len() < bytes_len {
+                    let got = (bytes.len() / record_size) as u16;
+                    let header_size = mem::size_of::<layout::Header>() as u64;
+                    let offset = section_position.offset + header_size + bytes.len() as u64;
+                    return Err(Error::TruncatedSection {
+                        position: Position {
+                            section: section_position.section,
+                            offset,
+                        },
+                        expected: len,
+                        got,
+                    });
+                }
+                let colors = zerocopy::LayoutVerified::<_, [layout::Color]>::new_slice(&bytes[..])
+                    .map(zerocopy::LayoutVerified::into_slice)
+                    .expect("buffer size must match the layout of `[Color]`");
+                palettes.set(header.projector_number, colors.to_vec());
+                prefetched = Some(io::Cursor::new(bytes));
+            }
+        }
+        let palette = match *palettes {
+            Some(ref palettes) => palettes.get(header.projector_number),
+            None => &DEFAULT_PALETTE[..],
+        };
+
+        // Create the reader for the format.
+        let pos = section_position;
         let reader = match header.format {
             layout::Format::COORDS_3D_INDEXED_COLOR => {
-                Coords3dIndexedColorReader::new(reader, len).into()
+                Coords3dIndexedColorReader::new(reader, len, pos).into()
             }
             layout::Format::COORDS_2D_INDEXED_COLOR => {
-                Coords2dIndexedColorReader::new(reader, len).into()
+                Coords2dIndexedColorReader::new(reader, len, pos).into()
+            }
+            layout::Format::COLOR_PALETTE => {
+                let mut palette_reader = ColorPaletteReader::new(reader, len, pos);
+                palette_reader.prefetched = prefetched;
+                palette_reader.into()
             }
-            layout::Format::COLOR_PALETTE => ColorPaletteReader::new(reader, len).into(),
             layout::Format::COORDS_3D_TRUE_COLOR => {
-                Coords3dTrueColorReader::new(reader, len).into()
+                Coords3dTrueColorReader::new(reader, len, pos).into()
             }
             layout::Format::COORDS_2D_TRUE_COLOR => {
-                Coords2dTrueColorReader::new(reader, len).into()
-            }
-            _ => {
-                let err_msg = "could not verify the layout of `Header`";
-                return Err(io::Error::new(io::ErrorKind::InvalidData, err_msg));
+                Coords2dTrueColorReader::new(reader, len, pos).into()
             }
+            _ => unreachable!("format verified by `record_size`"),
         };
 
-        Ok(Some(Section { header, reader }))
+        Ok(Some(Section {
+            header,
+            palette,
+            reader,
+        }))
     }
 }
 
@@ -222,13 +616,18 @@ where
     R: Read,
     T: LayoutBuffer,
 {
-    fn new(reader: R, len: u16) -> Self {
+    fn new(reader: R, len: u16, position: Position) -> Self {
         let buffer = T::empty();
+        let prefetched = None;
         let subsection_layout = std::marker::PhantomData;
+        let total = len;
         Self {
             reader,
             len,
+            total,
+            position,
             buffer,
+            prefetched,
             subsection_layout,
         }
     }
@@ -238,21 +637,142 @@ where
         self.len
     }
 
+    /// Whether or not all expected subsections have been read.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
     /// Read the next subsection.
-    pub fn read_next(&mut self) -> io::Result<Option<&T>> {
-        match self.len {
-            0 => return Ok(None),
-            ref mut n => *n -= 1,
+    ///
+    /// Returns a **TruncatedSection** error if the stream ends before the subsection is read.
+    pub fn read_next(&mut self) -> Result<Option<&T>, Error> {
+        if self.len == 0 {
+            return Ok(None);
         }
-        self.reader.read_exact(T::slice_mut(&mut self.buffer))?;
+        let buffer = T::slice_mut(&mut self.buffer);
+        let result = match self.prefetched {
+            Some(ref mut prefetched) => prefetched.read_exact(buffer),
+            None => self.reader.read_exact(buffer),
+        };
+        if let Err(err) = result {
+            // Avoid attempting to read the remainder of the section on drop.
+            let got = self.total - self.len;
+            self.len = 0;
+            return match err.kind() {
+                io::ErrorKind::UnexpectedEof => Err(Error::TruncatedSection {
+                    position: self.record_position(got),
+                    expected: self.total,
+                    got,
+                }),
+                _ => Err(Error::Io(err)),
+            };
+        }
+        self.len -= 1;
         let subsection = zerocopy::LayoutVerified::new(T::slice(&self.buffer))
             .map(zerocopy::LayoutVerified::into_ref)
-            .ok_or_else(|| {
-                let err_msg = "could not verify the layout of `Header`";
-                io::Error::new(io::ErrorKind::InvalidData, err_msg)
-            })?;
+            .expect("buffer size must match the subsection layout");
         Ok(Some(subsection))
     }
+
+    // The position of the record at the given index within the section.
+    fn record_position(&self, index: u16) -> Position {
+        let header_size = mem::size_of::<layout::Header>() as u64;
+        let record_size = mem::size_of::<T>() as u64;
+        Position {
+            section: self.position.section,
+            offset: self.position.offset + header_size + index as u64 * record_size,
+        }
+    }
+}
+
+impl<R, T> SubsectionReader<R, T>
+where
+    R: Read,
+    T: LayoutBuffer + zerocopy::AsBytes + Copy,
+{
+    /// Read as many of the remaining subsections as fit into the given slice.
+    ///
+    /// All subsections are read via a single read of the inner reader, making this significantly
+    /// more efficient than repeated calls to **read_next** for unbuffered readers.
+    ///
+    /// Returns the number of subsections read.
+    pub fn read_into(&mut self, subsections: &mut [T]) -> Result<usize, Error> {
+        let n = std::cmp::min(self.len as usize, subsections.len());
+        let bytes = zerocopy::AsBytes::as_bytes_mut(&mut subsections[..n]);
+        let result = match self.prefetched {
+            Some(ref mut prefetched) => read_fully(prefetched, bytes),
+            None => read_fully(&mut self.reader, bytes),
+        };
+        let got = self.total - self.len;
+        let read = match result {
+            Ok(read) => read,
+            Err(err) => {
+                self.len = 0;
+                return Err(Error::Io(err));
+            }
+        };
+        if read < bytes.len() {
+            // Avoid attempting to read the remainder of the section on drop.
+            let got = got + (read / mem::size_of::<T>()) as u16;
+            self.len = 0;
+            return Err(Error::TruncatedSection {
+                position: self.record_position(got),
+                expected: self.total,
+                got,
+            });
+        }
+        self.len -= n as u16;
+        Ok(n)
+    }
+
+    /// Read all remaining subsections onto the end of the given `Vec`.
+    ///
+    /// See **read_into**.
+    ///
+    /// Returns the number of subsections read.
+    pub fn read_all_into(&mut self, subsections: &mut Vec<T>) -> Result<usize, Error> {
+        let zeroed_buffer = T::empty();
+        let zeroed: T = *zerocopy::LayoutVerified::new(T::slice(&zeroed_buffer))
+            .map(zerocopy::LayoutVerified::into_ref)
+            .expect("buffer size must match the subsection layout");
+        let start = subsections.len();
+        subsections.resize(start + self.len as usize, zeroed);
+        let result = self.read_into(&mut subsections[start..]);
+        if result.is_err() {
+            subsections.truncate(start);
+        }
+        result
+    }
+}
+
+impl<R> Coords3dIndexedColorReader<R>
+where
+    R: Read,
+{
+    /// Read the next point with its color index resolved via the given palette.
+    ///
+    /// Typically the given palette is the `palette` of the **Section** that produced this reader.
+    pub fn read_next_resolved(
+        &mut self,
+        palette: &[layout::Color],
+    ) -> Result<Option<layout::Coords3dTrueColor>, Error> {
+        Ok(self.read_next()?.map(|p| p.to_true_color(palette)))
+    }
+}
+
+impl<R> Coords2dIndexedColorReader<R>
+where
+    R: Read,
+{
+    /// Read the next point with its color index resolved via the given palette.
+    ///
+    /// Typically the given palette is the `palette` of the **Section** that produced this reader.
+    pub fn read_next_resolved(
+        &mut self,
+        palette: &[layout::Color],
+    ) -> Result<Option<layout::Coords2dTrueColor>, Error> {
+        Ok(self.read_next()?.map(|p| p.to_true_color(palette)))
+    }
 }
 
 impl<R, T> Drop for SubsectionReader<R, T>
@@ -265,6 +785,40 @@ where
     }
 }
 
+impl<R> SubsectionReaderKind<R>
+where
+    R: Read,
+{
+    /// Read all points of the section, calling `f` with each point in turn.
+    ///
+    /// Allows for working with the four point layouts via the common **PointLayout** trait. Color
+    /// palette sections contain no points and `f` is never called.
+    pub fn for_each_point<F>(self, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(&dyn layout::PointLayout),
+    {
+        match self {
+            SubsectionReaderKind::Coords3dIndexedColor(r) => for_each_point(r, &mut f),
+            SubsectionReaderKind::Coords2dIndexedColor(r) => for_each_point(r, &mut f),
+            SubsectionReaderKind::ColorPalette(_) => Ok(()),
+            SubsectionReaderKind::Coords3dTrueColor(r) => for_each_point(r, &mut f),
+            SubsectionReaderKind::Coords2dTrueColor(r) => for_each_point(r, &mut f),
+        }
+    }
+}
+
+fn for_each_point<R, T, F>(mut reader: SubsectionReader<R, T>, f: &mut F) -> Result<(), Error>
+where
+    R: Read,
+    T: LayoutBuffer + layout::PointLayout,
+    F: FnMut(&dyn layout::PointLayout),
+{
+    while let Some(point) = reader.read_next()? {
+        f(point);
+    }
+    Ok(())
+}
+
 impl LayoutBuffer for layout::Coords3dIndexedColor {
     type Buffer = [u8; mem::size_of::<Self>()];
     fn empty() -> Self::Buffer {
@@ -330,6 +884,26 @@ impl LayoutBuffer for layout::Coords2dTrueColor {
     }
 }
 
+impl SubsectionLayout for layout::Coords3dIndexedColor {
+    const FORMAT: layout::Format = layout::Format::COORDS_3D_INDEXED_COLOR;
+}
+
+impl SubsectionLayout for layout::Coords2dIndexedColor {
+    const FORMAT: layout::Format = layout::Format::COORDS_2D_INDEXED_COLOR;
+}
+
+impl SubsectionLayout for layout::ColorPalette {
+    const FORMAT: layout::Format = layout::Format::COLOR_PALETTE;
+}
+
+impl SubsectionLayout for layout::Coords3dTrueColor {
+    const FORMAT: layout::Format = layout::Format::COORDS_3D_TRUE_COLOR;
+}
+
+impl SubsectionLayout for layout::Coords2dTrueColor {
+    const FORMAT: layout::Format = layout::Format::COORDS_2D_TRUE_COLOR;
+}
+
 impl<R> From<Coords3dIndexedColorReader<R>> for SubsectionReaderKind<R>
 where
     R: Read,
@@ -375,23 +949,189 @@ where
     }
 }
 
+// Fill as much of the buffer as possible, returning the number of bytes read.
+//
+// Only returns fewer bytes than the size of the buffer if the end of the stream is reached.
+fn read_fully<R>(reader: &mut R, buffer: &mut [u8]) -> io::Result<usize>
+where
+    R: Read,
+{
+    let mut n = 0;
+    while n < buffer.len() {
+        match reader.read(&mut buffer[n..]) {
+            Ok(0) => break,
+            Ok(len) => n += len,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => (),
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(n)
+}
+
+/// The size in bytes of a single record of the given format.
+///
+/// Returns `None` for format codes that are not defined by the specification.
+pub fn record_size(format: layout::Format) -> Option<usize> {
+    let size = match format {
+        layout::Format::COORDS_3D_INDEXED_COLOR => mem::size_of::<layout::Coords3dIndexedColor>(),
+        layout::Format::COORDS_2D_INDEXED_COLOR => mem::size_of::<layout::Coords2dIndexedColor>(),
+        layout::Format::COLOR_PALETTE => mem::size_of::<layout::ColorPalette>(),
+        layout::Format::COORDS_3D_TRUE_COLOR => mem::size_of::<layout::Coords3dTrueColor>(),
+        layout::Format::COORDS_2D_TRUE_COLOR => mem::size_of::<layout::Coords2dTrueColor>(),
+        _ => return None,
+    };
+    Some(size)
+}
+
 /// A `SectionReader` that reads from a buffered file.
 pub type BufFileSectionReader = SectionReader<io::BufReader<std::fs::File>>;
 
+/// Options for configuring the **SectionReader** produced by opening a file.
+///
+/// Mirrors the builder style of `std::fs::OpenOptions`.
+///
+/// ```rust,no_run
+/// let mut reader = ilda_idtf::OpenOptions::new()
+///     .strict(true)
+///     .palette_tracking(true)
+///     .open("/foo/bar")
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct OpenOptions {
+    strict: bool,
+    buffer_capacity: Option<usize>,
+    palette_tracking: bool,
+    max_records: u16,
+    recover: bool,
+}
+
+impl OpenOptions {
+    /// The default options, matching those used by **open**.
+    pub fn new() -> Self {
+        OpenOptions {
+            strict: false,
+            buffer_capacity: None,
+            palette_tracking: false,
+            max_records: u16::MAX,
+            recover: false,
+        }
+    }
+
+    /// See **SectionReader::strict**.
+    pub fn strict(&mut self, strict: bool) -> &mut Self {
+        self.strict = strict;
+        self
+    }
+
+    /// The capacity of the buffer used to read the file in bytes.
+    ///
+    /// Defaults to the default capacity of `std::io::BufReader`.
+    pub fn buffer_capacity(&mut self, capacity: usize) -> &mut Self {
+        self.buffer_capacity = Some(capacity);
+        self
+    }
+
+    /// See **SectionReader::palette_tracking**.
+    pub fn palette_tracking(&mut self, enabled: bool) -> &mut Self {
+        self.palette_tracking = enabled;
+        self
+    }
+
+    /// See **SectionReader::max_records**.
+    pub fn max_records(&mut self, max: u16) -> &mut Self {
+        self.max_records = max;
+        self
+    }
+
+    /// See **SectionReader::recover**.
+    pub fn recover(&mut self, recover: bool) -> &mut Self {
+        self.recover = recover;
+        self
+    }
+
+    /// Open the file at the given path as a `SectionReader` configured with these options.
+    pub fn open<P>(&self, path: P) -> io::Result<BufFileSectionReader>
+    where
+        P: AsRef<Path>,
+    {
+        self.open_path(path.as_ref())
+    }
+
+    fn open_path(&self, path: &Path) -> io::Result<BufFileSectionReader> {
+        let file = std::fs::File::open(path)?;
+        let buf_reader = match self.buffer_capacity {
+            Some(capacity) => io::BufReader::with_capacity(capacity, file),
+            None => io::BufReader::new(file),
+        };
+        let reader = SectionReader::new(buf_reader)
+            .strict(self.strict)
+            .palette_tracking(self.palette_tracking)
+            .max_records(self.max_records)
+            .recover(self.recover);
+        Ok(reader)
+    }
+}
+
+impl Default for OpenOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Round and clamp the coordinate to the range of `i16`. NaN produces 0.
+fn saturate(v: f32) -> i16 {
+    v.round() as i16
+}
+
+// Map the buffered header bytes to a **Header**.
+fn header_ref(buffer: &[u8; mem::size_of::<layout::Header>()]) -> &layout::Header {
+    zerocopy::LayoutVerified::new(&buffer[..])
+        .map(zerocopy::LayoutVerified::into_ref)
+        .expect("buffer size must match the layout of `Header`")
+}
+
+// Scan forward for the next ascii "ILDA", skipping at least the first byte of the buffer.
+//
+// Returns the number of bytes skipped and whether or not a whole header beginning with "ILDA" was
+// buffered before the end of the stream.
+fn resync<R>(reader: &mut R, buffer: &mut [u8]) -> io::Result<(u64, bool)>
+where
+    R: Read,
+{
+    let magic = &layout::Header::ILDA[..];
+    let mut skipped = 0;
+    loop {
+        // Find the first match, allowing for a partial match at the end of the buffer.
+        let shift = (1..buffer.len())
+            .find(|&i| {
+                let n = magic.len().min(buffer.len() - i);
+                buffer[i..i + n] == magic[..n]
+            })
+            .unwrap_or(buffer.len());
+        buffer.copy_within(shift.., 0);
+        let kept = buffer.len() - shift;
+        let n = read_fully(reader, &mut buffer[kept..])?;
+        skipped += shift as u64;
+        if n < shift {
+            return Ok((skipped + (kept + n) as u64, false));
+        }
+        if buffer.starts_with(magic) {
+            return Ok((skipped, true));
+        }
+    }
+}
+
 /// Open the file at the given path as a `SectionReader`.
 ///
 /// Returns a `SectionReader` that performs buffered reads on the file at the given path.
+///
+/// See **OpenOptions** for configuring the returned reader.
 pub fn open<P>(path: P) -> io::Result<BufFileSectionReader>
 where
     P: AsRef<Path>,
 {
-    open_path(path.as_ref())
-}
-
-fn open_path(path: &Path) -> io::Result<BufFileSectionReader> {
-    let file = std::fs::File::open(path).unwrap();
-    let buf_reader = std::io::BufReader::new(file);
-    Ok(SectionReader::new(buf_reader))
+    OpenOptions::new().open(path)
 }
 
 /// As recommended in the specification appendix.
@@ -0,0 +1,802 @@
+//! Items related to optimizing the points of a frame for display by laser scanners.
+//!
+//! The items in this module operate on slices of any of the point layouts via the **PathPoint**
+//! trait. As with the other items in this crate, the beam is considered to travel *to* each point,
+//! so that a point's status and color apply to the segment ending at that point.
+
+use crate::layout;
+use std::{f32::consts::PI, fmt};
+
+type I16 = zerocopy::byteorder::I16<layout::Endianness>;
+
+/// Point layouts that may be reordered and generated by the items in this module.
+pub trait PathPoint: layout::PointLayout + Copy {
+    /// Mutable access to the status of the point.
+    fn status_mut(&mut self) -> &mut layout::Status;
+    /// Move the point to the given position. The z coordinate is ignored by 2D layouts.
+    fn set_position(&mut self, position: [i16; 3]);
+}
+
+/// Options for inserting dwell points, giving scanners time to settle at the ends of lit paths
+/// and at corners.
+///
+/// Without blank dwell, the laser may switch on before the scanners have arrived at the start of
+/// a path or switch off after they have begun to leave its end, producing "tails". Without corner
+/// dwell, the scanners round off sharp corners and vertices appear dim.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DwellOptions {
+    blank_before: usize,
+    blank_after: usize,
+    corner_points: usize,
+}
+
+/// The error returned when the points of a frame would exceed the maximum number of records
+/// within a section, 65535.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct TooManyPoints;
+
+// The maximum distance within the tour over which the refinements of **optimize_paths** move
+// paths, along with the maximum number of passes over the tour, bounding their cost.
+const REFINE_WINDOW: usize = 32;
+const MAX_REFINE_PASSES: usize = 4;
+
+// The positions at which a path starts and ends.
+type Ends = ([i16; 3], [i16; 3]);
+
+// The position from which a path may be drawn, along with its index and whether it is reversed.
+type Entry = ([i16; 3], (usize, bool));
+
+// A run of lit points along with the position from which the beam travels to the first of them.
+#[derive(Clone)]
+struct Path<P> {
+    // A blanked point from which the first lit segment is drawn.
+    start: P,
+    lit: Vec<P>,
+}
+
+/// Reorder the lit paths of the frame to minimize the distance travelled while blanked.
+///
+/// The frame is split into paths of consecutive lit points. Each path begins from the point
+/// preceding its first lit point, as the segment to that point is drawn. The paths are ordered via
+/// a greedy nearest-neighbour search and, if `reverse` is `true`, reversed where doing so
+/// shortens the travel between them. The order is then refined by moving individual paths
+/// elsewhere in the order (Or-opt) and, if `reverse` is `true`, by reversing runs of paths
+/// (2-opt). Frames are drawn repeatedly, so the travel from the last path back to the first is
+/// included. If the result would not reduce the travel distance, the paths remain in their
+/// original order.
+///
+/// The nearest-neighbour search buckets paths within a grid, so typically costs little more than
+/// linear time. The refinements only move paths by up to 32 places in the order and make at most
+/// 4 passes, so their cost is linear in the number of paths. A frame of the maximum size is
+/// optimized in well under a second in release builds.
+///
+/// Each path is emitted as a single blanked point at its start followed by its lit points, so
+/// blanked points between paths are replaced. The `LAST_POINT` status bit is set on the final
+/// point only. Frames without lit points are returned unchanged other than their `LAST_POINT`
+/// status.
+///
+/// Returns an error if the frame would exceed 65535 points, e.g. as a blanked point is added
+/// before a first point that is lit.
+pub fn optimize_paths<P>(points: &[P], reverse: bool) -> Result<Vec<P>, TooManyPoints>
+where
+    P: PathPoint,
+{
+    let paths = split_paths(points);
+    let len = if paths.is_empty() {
+        points.len()
+    } else {
+        paths.iter().map(|path| 1 + path.lit.len()).sum()
+    };
+    if len > u16::MAX as usize {
+        return Err(TooManyPoints);
+    }
+    if paths.is_empty() {
+        let mut points = points.to_vec();
+        set_last_point(&mut points);
+        return Ok(points);
+    }
+
+    // Order the paths as `(index, reversed)` pairs.
+    let ends: Vec<Ends> = paths.iter().map(endpoints).collect();
+    let original: Vec<(usize, bool)> = (0..paths.len()).map(|i| (i, false)).collect();
+    let mut order = nearest_neighbour(&ends, reverse);
+    for _ in 0..MAX_REFINE_PASSES {
+        let reversed = reverse && two_opt(&ends, &mut order);
+        let moved = or_opt(&ends, &mut order);
+        if !reversed && !moved {
+            break;
+        }
+    }
+    if tour_length(&ends, &original) <= tour_length(&ends, &order) {
+        order = original;
+    }
+
+    let mut optimized = Vec::with_capacity(len);
+    for &(i, reversed) in &order {
+        let path = &paths[i];
+        if reversed {
+            reverse_path(path, &mut optimized);
+        } else {
+            optimized.push(path.start);
+            optimized.extend(&path.lit);
+        }
+    }
+    set_last_point(&mut optimized);
+    Ok(optimized)
+}
+
+impl DwellOptions {
+    /// The default options: 3 blanked points before and after each lit path and up to 4 points at
+    /// each corner.
+    pub fn new() -> Self {
+        DwellOptions {
+            blank_before: 3,
+            blank_after: 3,
+            corner_points: 4,
+        }
+    }
+
+    /// The number of additional blanked points at the start of each lit path, before the laser is
+    /// switched on.
+    pub fn blank_before(&mut self, n: usize) -> &mut Self {
+        self.blank_before = n;
+        self
+    }
+
+    /// The number of blanked points at the end of each lit path, after the laser is switched off.
+    pub fn blank_after(&mut self, n: usize) -> &mut Self {
+        self.blank_after = n;
+        self
+    }
+
+    /// The number of additional lit points at a corner that reverses direction.
+    ///
+    /// Corners receive a number of points proportional to their turn angle, rounded to the
+    /// nearest, so that a right angle receives half as many and straight lines receive none.
+    pub fn corner_points(&mut self, n: usize) -> &mut Self {
+        self.corner_points = n;
+        self
+    }
+
+    /// Insert dwell points into the frame.
+    ///
+    /// A lit path is a run of consecutive unblanked points, beginning from the point preceding
+    /// its first. Blank dwell repeats the position at which the path begins and the position at
+    /// which it ends with the `BLANKING` status bit set. Corner dwell repeats lit points with
+    /// their color. The order of the existing points is unchanged and the `LAST_POINT` status bit
+    /// is set on the final point only.
+    ///
+    /// Returns an error if the frame would exceed 65535 points.
+    pub fn apply<P>(&self, points: &[P]) -> Result<Vec<P>, TooManyPoints>
+    where
+        P: PathPoint,
+    {
+        let mut dwelled = Vec::with_capacity(points.len());
+        for (i, &p) in points.iter().enumerate() {
+            if p.status().is_blanking() {
+                push_n(&mut dwelled, p, 1)?;
+                continue;
+            }
+            let prev = i.checked_sub(1).map(|j| &points[j]);
+            let next = points.get(i + 1).filter(|q| !q.status().is_blanking());
+
+            // Settle at the start of the path, or at the lit first point, before unblanking.
+            if prev.is_none_or(|q| q.status().is_blanking()) {
+                let start = blanked(*prev.unwrap_or(&p));
+                push_n(&mut dwelled, start, self.blank_before)?;
+            }
+
+            push_n(&mut dwelled, p, 1)?;
+
+            match (prev, next) {
+                // Dwell at corners in proportion to the turn angle.
+                (Some(prev), Some(next)) => {
+                    let angle = turn_angle(position(prev), position(&p), position(next));
+                    let n = (self.corner_points as f32 * angle / PI).round() as usize;
+                    push_n(&mut dwelled, p, n)?;
+                }
+                // Settle at the end of the path after blanking.
+                (_, None) => push_n(&mut dwelled, blanked(p), self.blank_after)?,
+                _ => (),
+            }
+        }
+        set_last_point(&mut dwelled);
+        Ok(dwelled)
+    }
+}
+
+impl Default for DwellOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Subdivide segments such that no step between consecutive points exceeds the given distance.
+///
+/// The limit for each segment depends on whether the point at which it ends is blanked, allowing
+/// blanked travel to take larger steps than lit paths. Inserted points are copies of the point at
+/// which their segment ends, preserving its color and blanking, placed at evenly spaced positions
+/// along the segment. Limits that are not positive disable subdivision of the respective
+/// segments, while positive limits below 1.0 are treated as 1.0 as coordinates are integers. The
+/// `LAST_POINT` status bit is set on the final point only.
+///
+/// Frames are drawn repeatedly, so the move from the last point back to the first is subdivided
+/// too, with the inserted points appended to the end of the frame as copies of the first point.
+///
+/// Returns an error if the frame would exceed 65535 points.
+pub fn resample<P>(
+    points: &[P],
+    max_lit_step: f32,
+    max_blank_step: f32,
+) -> Result<Vec<P>, TooManyPoints>
+where
+    P: PathPoint,
+{
+    let mut resampled = Vec::with_capacity(points.len());
+    let limit = |p: &P| {
+        if p.status().is_blanking() {
+            max_blank_step
+        } else {
+            max_lit_step
+        }
+    };
+    let mut prev: Option<&P> = None;
+    for p in points {
+        if let Some(prev) = prev {
+            subdivide(&mut resampled, prev, p, limit(p))?;
+        }
+        push_n(&mut resampled, *p, 1)?;
+        prev = Some(p);
+    }
+    if let (Some(first), Some(last)) = (points.first(), points.last()) {
+        subdivide(&mut resampled, last, first, limit(first))?;
+    }
+    set_last_point(&mut resampled);
+    Ok(resampled)
+}
+
+/// Remove points that lie along the straight line between their neighbours.
+///
+/// A point is removed if it lies within `tolerance` of the segment between the last kept point
+/// and the following point, along with any points already removed since, and shares the color
+/// and status of the following point. As such, removal never changes the color or blanking with
+/// which any part of the frame is drawn. Points at the same position as a neighbour are
+/// considered intentional dwell and are kept, as are the first and last points. The `LAST_POINT`
+/// status bit is set on the final point only.
+///
+/// Rather than checking each removed point again as the segment grows, the directions from the
+/// last kept point that pass within `tolerance` of all removed points are tracked as a cone.
+/// Decimation is therefore linear in the number of points, though it may keep a small number of
+/// points that could have been removed.
+pub fn decimate<P>(points: &[P], tolerance: f32) -> Vec<P>
+where
+    P: PathPoint + PartialEq,
+{
+    let mut decimated: Vec<P> = Vec::with_capacity(points.len());
+    // The last kept point, the directions from it within tolerance of all points removed since,
+    // or `None` if no points have been removed, and the furthest distance of a removed point.
+    let mut anchor = [0; 3];
+    let mut corridor: Option<Cone> = None;
+    let mut reach: f64 = 0.0;
+    for (i, p) in points.iter().enumerate() {
+        let next = match points.get(i + 1) {
+            Some(next) if i > 0 => next,
+            _ => {
+                decimated.push(*p);
+                anchor = position(p);
+                corridor = None;
+                reach = 0.0;
+                continue;
+            }
+        };
+        let (a, b, c) = (anchor, position(p), position(next));
+        // Moving the point to its successor must leave it unchanged other than its position.
+        let mut moved = *p;
+        moved.set_position(c);
+        moved.status_mut().remove(layout::Status::LAST_POINT);
+        let mut next = *next;
+        next.status_mut().remove(layout::Status::LAST_POINT);
+
+        let mut redundant = None;
+        if moved == next && b != a && b != c {
+            let cone = Cone::within(a, b, tolerance as f64);
+            let narrowed = match corridor {
+                None => Some(cone),
+                Some(corridor) => corridor.intersect(&cone),
+            };
+            let (ab, ac) = (distance(a, b) as f64, distance(a, c) as f64);
+            let reach = reach.max(ab);
+            // The successor must lie beyond all removed points, in a direction passing near them.
+            if let Some(narrowed) = narrowed.filter(|n| ac > reach && n.contains(a, c)) {
+                redundant = Some((narrowed, reach));
+            }
+        }
+        match redundant {
+            Some((narrowed, r)) => {
+                corridor = Some(narrowed);
+                reach = r;
+            }
+            None => {
+                decimated.push(*p);
+                anchor = b;
+                corridor = None;
+                reach = 0.0;
+            }
+        }
+    }
+    set_last_point(&mut decimated);
+    decimated
+}
+
+/// The total distance travelled by the beam while blanked, i.e. the summed length of segments
+/// ending at blanked points.
+///
+/// As frames are drawn repeatedly, this includes the travel from the last point back to the first
+/// if the first point is blanked.
+pub fn blanked_distance<P>(points: &[P]) -> f32
+where
+    P: layout::PointLayout,
+{
+    let mut distance = 0.0;
+    for (i, p) in points.iter().enumerate() {
+        if p.status().is_blanking() {
+            let prev = if i == 0 {
+                points.last()
+            } else {
+                points.get(i - 1)
+            };
+            if let Some(prev) = prev {
+                distance += point_distance(prev, p);
+            }
+        }
+    }
+    distance
+}
+
+/// Clear the `LAST_POINT` status bit of all points other than the final point, on which it is
+/// set.
+pub fn set_last_point<P>(points: &mut [P])
+where
+    P: PathPoint,
+{
+    let len = points.len();
+    for (i, p) in points.iter_mut().enumerate() {
+        p.status_mut().set(layout::Status::LAST_POINT, i + 1 == len);
+    }
+}
+
+// Append `n` copies of the point, checking first that the points would not exceed the maximum
+// number of records within a section.
+// Push the points strictly between `from` and `to` such that no step exceeds `limit`, each a copy
+// of `to`.
+fn subdivide<P>(points: &mut Vec<P>, from: &P, to: &P, limit: f32) -> Result<(), TooManyPoints>
+where
+    P: PathPoint,
+{
+    let (a, b) = (position(from), position(to));
+    let steps = distance(a, b) / limit.max(1.0);
+    if limit <= 0.0 || steps <= 1.0 {
+        return Ok(());
+    }
+    let n = steps.ceil() as usize;
+    // Check the inserted points fit before generating them.
+    if points.len() + n - 1 > u16::MAX as usize {
+        return Err(TooManyPoints);
+    }
+    for i in 1..n {
+        let t = i as f32 / n as f32;
+        let lerp = |j: usize| a[j] as f32 + (b[j] as f32 - a[j] as f32) * t;
+        let mut q = *to;
+        q.set_position([0, 1, 2].map(|j| crate::saturate(lerp(j))));
+        points.push(q);
+    }
+    Ok(())
+}
+
+fn push_n<P>(points: &mut Vec<P>, p: P, n: usize) -> Result<(), TooManyPoints>
+where
+    P: Copy,
+{
+    if points.len().saturating_add(n) > u16::MAX as usize {
+        return Err(TooManyPoints);
+    }
+    points.extend(std::iter::repeat_n(p, n));
+    Ok(())
+}
+
+// The position of the point with z defaulting to 0 for 2D points.
+fn position<P>(p: &P) -> [i16; 3]
+where
+    P: layout::PointLayout,
+{
+    [p.x(), p.y(), p.z().unwrap_or(0)]
+}
+
+fn distance(a: [i16; 3], b: [i16; 3]) -> f32 {
+    let d = |i: usize| b[i] as f32 - a[i] as f32;
+    (d(0) * d(0) + d(1) * d(1) + d(2) * d(2)).sqrt()
+}
+
+// The angle in radians between the direction of travel into and out of `b`, from 0 for straight
+// lines to π for a reversal. Zero-length segments have no direction, so produce 0.
+fn turn_angle(a: [i16; 3], b: [i16; 3], c: [i16; 3]) -> f32 {
+    let sub = |p: [i16; 3], q: [i16; 3]| [0, 1, 2].map(|i| p[i] as f32 - q[i] as f32);
+    let (u, v) = (sub(b, a), sub(c, b));
+    let dot = u[0] * v[0] + u[1] * v[1] + u[2] * v[2];
+    let len = distance(a, b) * distance(b, c);
+    if len == 0.0 {
+        return 0.0;
+    }
+    (dot / len).clamp(-1.0, 1.0).acos()
+}
+
+// A set of directions within `angle` radians of the unit vector `axis`.
+#[derive(Copy, Clone)]
+struct Cone {
+    axis: [f64; 3],
+    angle: f64,
+}
+
+impl Cone {
+    // The directions from `a` of lines passing within `tolerance` of `b`, limited to less than a
+    // right angle so that `b` lies ahead of `a` along each.
+    fn within(a: [i16; 3], b: [i16; 3], tolerance: f64) -> Self {
+        let v = [0, 1, 2].map(|i| b[i] as f64 - a[i] as f64);
+        let len = dot(v, v).sqrt();
+        Cone {
+            axis: v.map(|c| c / len),
+            angle: (tolerance / len).clamp(0.0, 0.999).asin(),
+        }
+    }
+
+    // Whether the direction from `a` to `c` lies within the cone.
+    fn contains(&self, a: [i16; 3], c: [i16; 3]) -> bool {
+        let v = [0, 1, 2].map(|i| c[i] as f64 - a[i] as f64);
+        let cos = dot(v, self.axis) / dot(v, v).sqrt();
+        cos.clamp(-1.0, 1.0).acos() <= self.angle
+    }
+
+    // The largest cone within both, or `None` if they do not overlap.
+    fn intersect(&self, other: &Self) -> Option<Self> {
+        let between = dot(self.axis, other.axis).clamp(-1.0, 1.0).acos();
+        if between + other.angle <= self.angle {
+            return Some(*other);
+        } else if between + self.angle <= other.angle {
+            return Some(*self);
+        } else if between >= self.angle + other.angle {
+            return None;
+        }
+        // Rotate the axis towards the other, centering it within the overlap.
+        let rotation = (between + self.angle - other.angle) / 2.0;
+        let cos = between.cos();
+        let w = [0, 1, 2].map(|i| other.axis[i] - self.axis[i] * cos);
+        let w_len = dot(w, w).sqrt();
+        let axis = [0, 1, 2].map(|i| self.axis[i] * rotation.cos() + w[i] / w_len * rotation.sin());
+        let angle = (self.angle + other.angle - between) / 2.0;
+        Some(Cone { axis, angle })
+    }
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn point_distance<P>(a: &P, b: &P) -> f32
+where
+    P: layout::PointLayout,
+{
+    distance(position(a), position(b))
+}
+
+fn blanked<P>(mut p: P) -> P
+where
+    P: PathPoint,
+{
+    p.status_mut().insert(layout::Status::BLANKING);
+    p
+}
+
+// Split the frame into its lit paths.
+fn split_paths<P>(points: &[P]) -> Vec<Path<P>>
+where
+    P: PathPoint,
+{
+    let mut paths: Vec<Path<P>> = vec![];
+    let mut prev: Option<&P> = None;
+    for p in points {
+        if !p.status().is_blanking() {
+            let continues = prev.is_some_and(|q| !q.status().is_blanking());
+            match paths.last_mut() {
+                Some(path) if continues => path.lit.push(*p),
+                _ => paths.push(Path {
+                    // A lit first point is drawn as a dot, so begins from its own position.
+                    start: blanked(*prev.unwrap_or(p)),
+                    lit: vec![*p],
+                }),
+            }
+        }
+        prev = Some(p);
+    }
+    paths
+}
+
+// The positions at which the path starts and ends.
+fn endpoints<P>(path: &Path<P>) -> Ends
+where
+    P: layout::PointLayout,
+{
+    let start = position(&path.start);
+    let end = position(
+        path.lit
+            .last()
+            .expect("paths contain at least one lit point"),
+    );
+    (start, end)
+}
+
+// The start and end of the path at `entry` within the tour, accounting for reversal.
+fn oriented(ends: &[Ends], (i, reversed): (usize, bool)) -> Ends {
+    let (start, end) = ends[i];
+    if reversed {
+        (end, start)
+    } else {
+        (start, end)
+    }
+}
+
+// The blanked distance travelled when drawing the paths in the given order, including the return
+// to the beginning.
+fn tour_length(ends: &[Ends], order: &[(usize, bool)]) -> f32 {
+    let mut length = 0.0;
+    for (k, &entry) in order.iter().enumerate() {
+        let next = order[(k + 1) % order.len()];
+        length += distance(oriented(ends, entry).1, oriented(ends, next).0);
+    }
+    length
+}
+
+// Greedily visit the nearest remaining path, starting from the first.
+//
+// The start of each path, and its end if it may be reversed, are bucketed within a uniform grid
+// so that each search need only visit the cells surrounding the current position.
+fn nearest_neighbour(ends: &[Ends], reverse: bool) -> Vec<(usize, bool)> {
+    // With a single path there is nothing to search for.
+    if ends.len() < 2 {
+        return vec![(0, false)];
+    }
+    let mut entries: Vec<Entry> = vec![];
+    for (i, &(start, end)) in ends.iter().enumerate().skip(1) {
+        entries.push((start, (i, false)));
+        if reverse {
+            entries.push((end, (i, true)));
+        }
+    }
+    let mut grid = Grid::new(&entries);
+    let mut order = Vec::with_capacity(ends.len());
+    order.push((0, false));
+    let mut end = ends[0].1;
+    while let Some(entry) = grid.nearest(end) {
+        let (start, path_end) = ends[entry.0];
+        grid.remove(start, entry.0);
+        if reverse {
+            grid.remove(path_end, entry.0);
+        }
+        order.push(entry);
+        end = oriented(ends, entry).1;
+    }
+    order
+}
+
+// Repeatedly reverse runs of paths within the tour while doing so shortens it.
+//
+// Only runs of up to `REFINE_WINDOW` paths are considered.
+fn two_opt(ends: &[Ends], order: &mut [(usize, bool)]) -> bool {
+    let n = order.len();
+    let mut improved = false;
+    // The first path remains fixed as the tour is a cycle.
+    for i in 1..n {
+        for j in i..n.min(i + REFINE_WINDOW) {
+            let prev_end = oriented(ends, order[i - 1]).1;
+            let start_i = oriented(ends, order[i]).0;
+            let end_j = oriented(ends, order[j]).1;
+            let next_start = oriented(ends, order[(j + 1) % n]).0;
+            let before = distance(prev_end, start_i) + distance(end_j, next_start);
+            let after = distance(prev_end, end_j) + distance(start_i, next_start);
+            if after + 1e-3 < before {
+                order[i..=j].reverse();
+                for entry in &mut order[i..=j] {
+                    entry.1 = !entry.1;
+                }
+                improved = true;
+            }
+        }
+    }
+    improved
+}
+
+// Repeatedly move single paths elsewhere within the tour while doing so shortens it, retaining
+// their direction.
+//
+// Only positions within `REFINE_WINDOW` paths of the original are considered.
+fn or_opt(ends: &[Ends], order: &mut [(usize, bool)]) -> bool {
+    let n = order.len();
+    let mut improved = false;
+    for i in 1..n {
+        let (start, end) = oriented(ends, order[i]);
+        let prev_end = oriented(ends, order[i - 1]).1;
+        let next_start = oriented(ends, order[(i + 1) % n]).0;
+        let removed =
+            distance(prev_end, start) + distance(end, next_start) - distance(prev_end, next_start);
+        let mut best: Option<(f32, usize)> = None;
+        for j in i.saturating_sub(REFINE_WINDOW)..n.min(i + REFINE_WINDOW) {
+            if j + 1 == i || j == i {
+                continue;
+            }
+            // Insert between the path at `j` and its successor.
+            let a = oriented(ends, order[j]).1;
+            let b = oriented(ends, order[(j + 1) % n]).0;
+            let added = distance(a, start) + distance(end, b) - distance(a, b);
+            if added + 1e-3 < removed && best.is_none_or(|(d, _)| added < d) {
+                best = Some((added, j));
+            }
+        }
+        if let Some((_, j)) = best {
+            if j > i {
+                order[i..=j].rotate_left(1);
+            } else {
+                order[j + 1..=i].rotate_right(1);
+            }
+            improved = true;
+        }
+    }
+    improved
+}
+
+// Entries bucketed by their x and y coordinates for nearest neighbour searches.
+struct Grid {
+    min: [i32; 2],
+    // The width and height of each cell.
+    cell: [i32; 2],
+    len: i32,
+    cells: Vec<Vec<Entry>>,
+}
+
+impl Grid {
+    fn new(entries: &[Entry]) -> Self {
+        let mut min = [i32::MAX; 2];
+        let mut max = [i32::MIN; 2];
+        for (p, _) in entries {
+            for i in 0..2 {
+                min[i] = min[i].min(p[i] as i32);
+                max[i] = max[i].max(p[i] as i32);
+            }
+        }
+        // Aim for roughly one entry per cell.
+        let len = ((entries.len() as f32).sqrt().ceil() as i32).clamp(1, 256);
+        let cell = [0, 1].map(|i| ((max[i] - min[i]).max(0) / len + 1).max(1));
+        let mut grid = Grid {
+            min,
+            cell,
+            len,
+            cells: vec![vec![]; (len * len) as usize],
+        };
+        for &entry in entries {
+            let [x, y] = grid.cell_of(entry.0);
+            grid.cells[(y * len + x) as usize].push(entry);
+        }
+        grid
+    }
+
+    // The cell containing the position, clamped to the grid.
+    fn cell_of(&self, p: [i16; 3]) -> [i32; 2] {
+        [0, 1].map(|i| ((p[i] as i32 - self.min[i]) / self.cell[i]).clamp(0, self.len - 1))
+    }
+
+    // The nearest remaining entry to the position.
+    fn nearest(&self, p: [i16; 3]) -> Option<(usize, bool)> {
+        let [cx, cy] = self.cell_of(p);
+        let mut nearest: Option<(f32, (usize, bool))> = None;
+        for ring in 0..self.len {
+            for y in (cy - ring).max(0)..=(cy + ring).min(self.len - 1) {
+                for x in (cx - ring).max(0)..=(cx + ring).min(self.len - 1) {
+                    // Only visit the cells on the perimeter of the ring.
+                    if (x - cx).abs() != ring && (y - cy).abs() != ring {
+                        continue;
+                    }
+                    for &(q, entry) in &self.cells[(y * self.len + x) as usize] {
+                        let d = distance(p, q);
+                        if nearest.is_none_or(|(nd, _)| d < nd) {
+                            nearest = Some((d, entry));
+                        }
+                    }
+                }
+            }
+            // Entries beyond this ring lie at least `ring` cells away.
+            let bound = ring as f32 * self.cell[0].min(self.cell[1]) as f32;
+            if nearest.is_some_and(|(d, _)| d <= bound) {
+                break;
+            }
+        }
+        nearest.map(|(_, entry)| entry)
+    }
+
+    // Remove the entries of the path from the cell containing the position.
+    fn remove(&mut self, p: [i16; 3], path: usize) {
+        let [x, y] = self.cell_of(p);
+        self.cells[(y * self.len + x) as usize].retain(|&(_, (i, _))| i != path);
+    }
+}
+
+// Emit the path in reverse, such that each segment retains its original color.
+fn reverse_path<P>(path: &Path<P>, points: &mut Vec<P>)
+where
+    P: PathPoint,
+{
+    let lit = &path.lit;
+    let last = lit.last().expect("paths contain at least one lit point");
+    let mut start = blanked(path.start);
+    start.set_position(position(last));
+    points.push(start);
+    // The segment to each position takes the color of the segment that originally left it.
+    for k in (0..lit.len()).rev() {
+        let mut p = lit[k];
+        let to = if k == 0 { &path.start } else { &lit[k - 1] };
+        p.set_position(position(to));
+        points.push(p);
+    }
+}
+
+fn set_coords_3d(coords: &mut layout::Coords3d, [x, y, z]: [i16; 3]) {
+    coords.x = I16::new(x);
+    coords.y = I16::new(y);
+    coords.z = I16::new(z);
+}
+
+fn set_coords_2d(coords: &mut layout::Coords2d, [x, y, _]: [i16; 3]) {
+    coords.x = I16::new(x);
+    coords.y = I16::new(y);
+}
+
+impl PathPoint for layout::Coords3dIndexedColor {
+    fn status_mut(&mut self) -> &mut layout::Status {
+        &mut self.status
+    }
+    fn set_position(&mut self, position: [i16; 3]) {
+        set_coords_3d(&mut self.coords, position);
+    }
+}
+
+impl PathPoint for layout::Coords2dIndexedColor {
+    fn status_mut(&mut self) -> &mut layout::Status {
+        &mut self.status
+    }
+    fn set_position(&mut self, position: [i16; 3]) {
+        set_coords_2d(&mut self.coords, position);
+    }
+}
+
+impl PathPoint for layout::Coords3dTrueColor {
+    fn status_mut(&mut self) -> &mut layout::Status {
+        &mut self.status
+    }
+    fn set_position(&mut self, position: [i16; 3]) {
+        set_coords_3d(&mut self.coords, position);
+    }
+}
+
+impl PathPoint for layout::Coords2dTrueColor {
+    fn status_mut(&mut self) -> &mut layout::Status {
+        &mut self.status
+    }
+    fn set_position(&mut self, position: [i16; 3]) {
+        set_coords_2d(&mut self.coords, position);
+    }
+}
+
+impl fmt::Display for TooManyPoints {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "frame would exceed the maximum of 65535 points")
+    }
+}
+
+impl std::error::Error for TooManyPoints {}
@@ -0,0 +1,88 @@
+//! Items related to tracking the color palettes used to resolve indexed colors.
+
+use crate::{layout, DEFAULT_PALETTE};
+use std::collections::HashMap;
+
+/// Tracks the most recently read color palette for each projector.
+///
+/// Projectors for which no palette has been read fall back to the **DEFAULT_PALETTE**.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Palettes {
+    by_projector: HashMap<u8, Vec<layout::Color>>,
+}
+
+impl Palettes {
+    /// Begin with the **DEFAULT_PALETTE** in effect for all projectors.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The palette currently in effect for the given projector.
+    pub fn get(&self, projector: u8) -> &[layout::Color] {
+        self.by_projector
+            .get(&projector)
+            .map(|colors| &colors[..])
+            .unwrap_or(&DEFAULT_PALETTE[..])
+    }
+
+    /// Replace the palette in effect for the given projector.
+    pub fn set(&mut self, projector: u8, colors: Vec<layout::Color>) {
+        self.by_projector.insert(projector, colors);
+    }
+
+    /// Revert all projectors to the **DEFAULT_PALETTE**.
+    pub fn clear(&mut self) {
+        self.by_projector.clear();
+    }
+
+    /// Resolve the given color index for the given projector.
+    ///
+    /// See [**resolve_color**](./fn.resolve_color.html).
+    pub fn resolve(&self, projector: u8, index: u8, status: layout::Status) -> layout::Color {
+        resolve_color(self.get(projector), index, status)
+    }
+}
+
+/// Resolve the given color index via the given palette.
+///
+/// As per the specification, the blanking bit takes precedence over the color from the palette.
+/// Blanked points and indices beyond the end of the palette resolve to black.
+pub fn resolve_color(
+    palette: &[layout::Color],
+    index: u8,
+    status: layout::Status,
+) -> layout::Color {
+    if status.is_blanking() {
+        return layout::Color::BLACK;
+    }
+    palette
+        .get(index as usize)
+        .copied()
+        .unwrap_or(layout::Color::BLACK)
+}
+
+impl layout::Coords3dIndexedColor {
+    /// Resolve the color index via the given palette, producing the equivalent true color point.
+    ///
+    /// The color is resolved via [**resolve_color**](../fn.resolve_color.html).
+    pub fn to_true_color(&self, palette: &[layout::Color]) -> layout::Coords3dTrueColor {
+        layout::Coords3dTrueColor {
+            coords: self.coords,
+            status: self.status,
+            color: resolve_color(palette, self.color_index, self.status),
+        }
+    }
+}
+
+impl layout::Coords2dIndexedColor {
+    /// Resolve the color index via the given palette, producing the equivalent true color point.
+    ///
+    /// The color is resolved via [**resolve_color**](../fn.resolve_color.html).
+    pub fn to_true_color(&self, palette: &[layout::Color]) -> layout::Coords2dTrueColor {
+        layout::Coords2dTrueColor {
+            coords: self.coords,
+            status: self.status,
+            color: resolve_color(palette, self.color_index, self.status),
+        }
+    }
+}
@@ -0,0 +1,455 @@
+//! Items related to rasterizing frames on the CPU, e.g. for generating thumbnails and previews.
+//!
+//! Lit segments are drawn as anti-aliased lines that blend additively, so that overlapping
+//! segments and corners appear brighter in the way that they do when projected. As with SVG
+//! export, a segment takes the color of the point at which it ends and segments ending at blanked
+//! points are not drawn. A lit first point is drawn as a dot.
+//!
+//! IDTF coordinates are scaled uniformly to fit the image, centered, with y flipped to increase
+//! downwards. The z coordinate of 3D points is ignored.
+//!
+//! With the `png` feature enabled, rendered **Image**s may be encoded as PNG and sequences of
+//! frames may be exported as animated PNG (APNG). With the `gif` feature enabled, sequences of
+//! frames may be exported as animated GIF. See **Animation::sequences** for grouping the frames of
+//! a file into sequences.
+
+use crate::{animation, layout};
+use std::fmt;
+#[cfg(any(feature = "gif", feature = "png"))]
+use std::{convert::TryFrom, fs, io, path::Path};
+
+// The maximum number of pixels within an image, bounding the memory required for rendering.
+const MAX_PIXELS: u64 = 8192 * 8192;
+
+/// Options for rendering frames to an **Image**.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderOptions {
+    width: u32,
+    height: u32,
+    line_width: f32,
+    background: Option<layout::Color>,
+    glow: Option<f32>,
+    dwell: bool,
+    frame_rate: f32,
+}
+
+/// An 8-bit RGBA image.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Image {
+    /// The width of the image in pixels.
+    pub width: u32,
+    /// The height of the image in pixels.
+    pub height: u32,
+    /// The RGBA bytes of each pixel in row-major order, starting from the top left.
+    pub pixels: Vec<u8>,
+}
+
+/// The error returned when the size of an image exceeds the maximum of 67108864 pixels, e.g.
+/// 8192x8192.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ImageTooLarge;
+
+// The minimal information required to draw a point, in pixel coordinates.
+#[derive(Copy, Clone)]
+struct RenderPoint {
+    x: f32,
+    y: f32,
+    color: layout::Color,
+    blanking: bool,
+}
+
+// Accumulates additive light in linear RGB.
+struct Canvas {
+    width: u32,
+    height: u32,
+    light: Vec<[f32; 3]>,
+}
+
+impl RenderOptions {
+    /// The default options: a 512x512 pixel image on a black background with 1.5 pixel wide lines,
+    /// no glow, no dwell visualization and 30 frames per second for animations.
+    pub fn new() -> Self {
+        RenderOptions {
+            width: 512,
+            height: 512,
+            line_width: 1.5,
+            background: Some(layout::Color::BLACK),
+            glow: None,
+            dwell: false,
+            frame_rate: 30.0,
+        }
+    }
+
+    /// The width and height of the image in pixels.
+    ///
+    /// Images may contain at most 67108864 pixels, e.g. 8192x8192. Rendering larger images
+    /// produces an **ImageTooLarge** error.
+    pub fn size(&mut self, width: u32, height: u32) -> &mut Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// The width of lines in pixels.
+    pub fn line_width(&mut self, width: f32) -> &mut Self {
+        self.line_width = width;
+        self
+    }
+
+    /// The background color, or `None` for a transparent background.
+    ///
+    /// With a transparent background, the opacity of each pixel is that of its brightest channel.
+    pub fn background(&mut self, color: Option<layout::Color>) -> &mut Self {
+        self.background = color;
+        self
+    }
+
+    /// Surround lit segments with a soft glow of the given radius in pixels, or `None` to disable.
+    pub fn glow(&mut self, radius: Option<f32>) -> &mut Self {
+        self.glow = radius;
+        self
+    }
+
+    /// Whether or not to visualize dwell.
+    ///
+    /// When enabled, runs of consecutive lit points at the same position are drawn as a dot whose
+    /// size grows with the number of points, indicating where the beam lingers.
+    pub fn dwell(&mut self, dwell: bool) -> &mut Self {
+        self.dwell = dwell;
+        self
+    }
+
+    /// The number of frames per second when exporting animations.
+    pub fn frame_rate(&mut self, fps: f32) -> &mut Self {
+        self.frame_rate = fps;
+        self
+    }
+
+    /// Render the frame.
+    ///
+    /// Returns an error if the image would exceed the maximum number of pixels.
+    pub fn frame(&self, frame: &animation::Frame) -> Result<Image, ImageTooLarge> {
+        let points = frame
+            .points
+            .iter()
+            .map(|p| self.render_point(p.x, p.y, p.color, p.blanking));
+        self.render(points)
+    }
+
+    /// Render the given frame points.
+    ///
+    /// Indexed colors are resolved via the given palette. Returns an error if the image would
+    /// exceed the maximum number of pixels.
+    pub fn points<P>(&self, points: &[P], palette: &[layout::Color]) -> Result<Image, ImageTooLarge>
+    where
+        P: layout::PointLayout,
+    {
+        let points = points.iter().map(|p| {
+            let blanking = p.status().is_blanking();
+            self.render_point(p.x(), p.y(), p.color(palette), blanking)
+        });
+        self.render(points)
+    }
+
+    /// Export the frames as a looping animated GIF.
+    ///
+    /// Colors are quantized to a palette of 256 per frame. GIF frame delays are specified in
+    /// hundredths of a second, so the frame rate is rounded accordingly.
+    #[cfg(feature = "gif")]
+    pub fn write_gif<'a, I, W>(&self, frames: I, writer: W) -> io::Result<()>
+    where
+        I: IntoIterator<Item = &'a animation::Frame>,
+        W: io::Write,
+    {
+        let too_large = || invalid_input("GIF dimensions are limited to 65535 pixels");
+        let width = u16::try_from(self.width).map_err(|_| too_large())?;
+        let height = u16::try_from(self.height).map_err(|_| too_large())?;
+        self.verify_size().map_err(size_error)?;
+        let delay = (100.0 / self.frame_rate)
+            .round()
+            .clamp(1.0, u16::MAX as f32) as u16;
+        // Check for frames before the encoder writes the GIF header.
+        let mut frames = frames.into_iter().peekable();
+        if frames.peek().is_none() {
+            return Err(invalid_input("an animation requires at least one frame"));
+        }
+        let mut encoder = gif::Encoder::new(writer, width, height, &[]).map_err(gif_error)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(gif_error)?;
+        for frame in frames {
+            let mut image = self.frame(frame).map_err(size_error)?;
+            let mut gif_frame = gif::Frame::from_rgba_speed(width, height, &mut image.pixels, 10);
+            gif_frame.delay = delay;
+            // Replace the previous frame rather than drawing over it.
+            gif_frame.dispose = gif::DisposalMethod::Background;
+            encoder.write_frame(&gif_frame).map_err(gif_error)?;
+        }
+        Ok(())
+    }
+
+    /// Export the frames as a looping animated GIF to the file at the given path.
+    #[cfg(feature = "gif")]
+    pub fn save_gif<'a, I, P>(&self, frames: I, path: P) -> io::Result<()>
+    where
+        I: IntoIterator<Item = &'a animation::Frame>,
+        P: AsRef<Path>,
+    {
+        let file = fs::File::create(path)?;
+        self.write_gif(frames, io::BufWriter::new(file))
+    }
+
+    /// Export the frames as a looping animated PNG.
+    #[cfg(feature = "png")]
+    pub fn write_apng<'a, I, W>(&self, frames: I, writer: W) -> io::Result<()>
+    where
+        I: IntoIterator<Item = &'a animation::Frame>,
+        I::IntoIter: ExactSizeIterator,
+        W: io::Write,
+    {
+        let frames = frames.into_iter();
+        let num_frames =
+            u32::try_from(frames.len()).map_err(|_| invalid_input("too many frames"))?;
+        if num_frames == 0 {
+            return Err(invalid_input("an animation requires at least one frame"));
+        }
+        self.verify_size().map_err(size_error)?;
+        // Express the delay as a fraction of a second with a precision of 1/100th of a frame.
+        let den = (self.frame_rate * 100.0)
+            .round()
+            .clamp(1.0, u16::MAX as f32) as u16;
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_animated(num_frames, 0).map_err(png_error)?;
+        encoder.set_frame_delay(100, den).map_err(png_error)?;
+        encoder
+            .set_dispose_op(png::DisposeOp::Background)
+            .map_err(png_error)?;
+        let mut writer = encoder.write_header().map_err(png_error)?;
+        for frame in frames {
+            let image = self.frame(frame).map_err(size_error)?;
+            writer.write_image_data(&image.pixels).map_err(png_error)?;
+        }
+        writer.finish().map_err(png_error)
+    }
+
+    /// Export the frames as a looping animated PNG to the file at the given path.
+    #[cfg(feature = "png")]
+    pub fn save_apng<'a, I, P>(&self, frames: I, path: P) -> io::Result<()>
+    where
+        I: IntoIterator<Item = &'a animation::Frame>,
+        I::IntoIter: ExactSizeIterator,
+        P: AsRef<Path>,
+    {
+        let file = fs::File::create(path)?;
+        self.write_apng(frames, io::BufWriter::new(file))
+    }
+
+    // Map the point's coordinates into the image.
+    fn render_point(&self, x: i16, y: i16, color: layout::Color, blanking: bool) -> RenderPoint {
+        let size = self.width.min(self.height) as f32;
+        let scale = size / 65536.0;
+        RenderPoint {
+            x: self.width as f32 / 2.0 + (x as f32 + 0.5) * scale,
+            y: self.height as f32 / 2.0 - (y as f32 + 0.5) * scale,
+            color,
+            blanking,
+        }
+    }
+
+    // Ensure the image does not exceed the maximum number of pixels.
+    fn verify_size(&self) -> Result<(), ImageTooLarge> {
+        if self.width as u64 * self.height as u64 > MAX_PIXELS {
+            return Err(ImageTooLarge);
+        }
+        Ok(())
+    }
+
+    fn render<I>(&self, points: I) -> Result<Image, ImageTooLarge>
+    where
+        I: IntoIterator<Item = RenderPoint>,
+    {
+        self.verify_size()?;
+        let mut canvas = Canvas {
+            width: self.width,
+            height: self.height,
+            light: vec![[0.0; 3]; self.width as usize * self.height as usize],
+        };
+        let radius = self.line_width.max(0.0) / 2.0;
+        let glow = self.glow.filter(|&r| r > 0.0);
+
+        let mut prev: Option<RenderPoint> = None;
+        let mut dwell = 0u32;
+        for p in points {
+            if !p.blanking {
+                let from = prev.unwrap_or(p);
+                canvas.segment(from, p, radius, glow);
+                // Track runs of lit points at the same position.
+                let same = prev.is_some_and(|q| !q.blanking && q.x == p.x && q.y == p.y);
+                dwell = if same { dwell + 1 } else { 1 };
+                if self.dwell && dwell > 1 {
+                    let dot = radius.max(0.5) * (dwell as f32).sqrt();
+                    canvas.segment(p, p, dot, None);
+                }
+            }
+            prev = Some(p);
+        }
+
+        Ok(canvas.image(self.background))
+    }
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Canvas {
+    // Additively draw a line of the given radius with round caps, anti-aliased over one pixel.
+    fn segment(&mut self, a: RenderPoint, b: RenderPoint, radius: f32, glow: Option<f32>) {
+        let reach = radius + 1.0 + glow.map_or(0.0, |g| g * 2.0);
+        let x0 = (a.x.min(b.x) - reach).floor().max(0.0) as i64;
+        let y0 = (a.y.min(b.y) - reach).floor().max(0.0) as i64;
+        let x1 = ((a.x.max(b.x) + reach).ceil() as i64).min(self.width as i64);
+        let y1 = ((a.y.max(b.y) + reach).ceil() as i64).min(self.height as i64);
+        let color = [b.color.red, b.color.green, b.color.blue].map(|c| c as f32 / 255.0);
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let len2 = dx * dx + dy * dy;
+        for py in y0..y1 {
+            for px in x0..x1 {
+                // The distance from the pixel center to the nearest point on the segment.
+                let (cx, cy) = (px as f32 + 0.5, py as f32 + 0.5);
+                let t = if len2 > 0.0 {
+                    (((cx - a.x) * dx + (cy - a.y) * dy) / len2).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let d = (cx - (a.x + dx * t)).hypot(cy - (a.y + dy * t));
+                let mut intensity = (radius + 0.5 - d).clamp(0.0, 1.0);
+                if let Some(g) = glow {
+                    intensity += 0.35 * (-(d / g) * (d / g)).exp();
+                }
+                if intensity > 0.0 {
+                    let pixel = &mut self.light[py as usize * self.width as usize + px as usize];
+                    for (p, c) in pixel.iter_mut().zip(&color) {
+                        *p += c * intensity;
+                    }
+                }
+            }
+        }
+    }
+
+    fn image(self, background: Option<layout::Color>) -> Image {
+        let mut pixels = Vec::with_capacity(self.light.len() * 4);
+        for light in &self.light {
+            match background {
+                Some(bg) => {
+                    let bg = [bg.red, bg.green, bg.blue];
+                    for (l, b) in light.iter().zip(&bg) {
+                        pixels.push((*b as f32 + l * 255.0).round().min(255.0) as u8);
+                    }
+                    pixels.push(255);
+                }
+                None => {
+                    // Treat the light as premultiplied by the opacity of its brightest channel.
+                    let alpha = light.iter().cloned().fold(0.0, f32::max).min(1.0);
+                    for l in light {
+                        let c = if alpha > 0.0 {
+                            (l / alpha).min(1.0)
+                        } else {
+                            0.0
+                        };
+                        pixels.push((c * 255.0).round() as u8);
+                    }
+                    pixels.push((alpha * 255.0).round() as u8);
+                }
+            }
+        }
+        Image {
+            width: self.width,
+            height: self.height,
+            pixels,
+        }
+    }
+}
+
+impl Image {
+    /// The RGBA color of the pixel at the given column and row.
+    ///
+    /// Returns `None` if the coordinates lie outside the image.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        let p = &self.pixels[i..i + 4];
+        Some([p[0], p[1], p[2], p[3]])
+    }
+
+    /// Encode the image as PNG.
+    #[cfg(feature = "png")]
+    pub fn write_png<W>(&self, writer: W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(png_error)?;
+        writer.write_image_data(&self.pixels).map_err(png_error)?;
+        writer.finish().map_err(png_error)
+    }
+
+    /// Encode the image as PNG and write it to the file at the given path.
+    #[cfg(feature = "png")]
+    pub fn save_png<P>(&self, path: P) -> io::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let file = fs::File::create(path)?;
+        self.write_png(io::BufWriter::new(file))
+    }
+}
+
+/// Render the frame with the default **RenderOptions**.
+pub fn render_frame(frame: &animation::Frame) -> Image {
+    RenderOptions::new()
+        .frame(frame)
+        .expect("the default size is within the maximum")
+}
+
+impl fmt::Display for ImageTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "image exceeds the maximum of {} pixels", MAX_PIXELS)
+    }
+}
+
+impl std::error::Error for ImageTooLarge {}
+
+#[cfg(any(feature = "gif", feature = "png"))]
+fn size_error(err: ImageTooLarge) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, err)
+}
+
+#[cfg(any(feature = "gif", feature = "png"))]
+fn invalid_input(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+#[cfg(feature = "gif")]
+fn gif_error(err: gif::EncodingError) -> io::Error {
+    match err {
+        gif::EncodingError::Io(err) => err,
+        err => io::Error::other(err),
+    }
+}
+
+#[cfg(feature = "png")]
+fn png_error(err: png::EncodingError) -> io::Error {
+    match err {
+        png::EncodingError::IoError(err) => err,
+        err => io::Error::other(err),
+    }
+}
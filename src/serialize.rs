@@ -0,0 +1,239 @@
+//! Human-friendly serde implementations for the layout types.
+//!
+//! Enabled via the `serde` feature. Formats are represented by name, names as strings, status
+//! bytes as booleans and big-endian integers as plain numbers.
+
+use crate::layout;
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+use std::fmt;
+
+// The formats defined by the specification along with their names.
+const FORMATS: [layout::Format; 5] = [
+    layout::Format::COORDS_3D_INDEXED_COLOR,
+    layout::Format::COORDS_2D_INDEXED_COLOR,
+    layout::Format::COLOR_PALETTE,
+    layout::Format::COORDS_3D_TRUE_COLOR,
+    layout::Format::COORDS_2D_TRUE_COLOR,
+];
+const FORMAT_NAMES: &[&str] = &[
+    "Coords3dIndexedColor",
+    "Coords2dIndexedColor",
+    "ColorPalette",
+    "Coords3dTrueColor",
+    "Coords2dTrueColor",
+];
+
+/// (De)serialize a big-endian `I16` as a plain number.
+pub mod be_i16 {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    type I16 = zerocopy::byteorder::I16<crate::layout::Endianness>;
+
+    pub fn serialize<S>(v: &I16, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_i16(v.get())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<I16, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        i16::deserialize(deserializer).map(I16::new)
+    }
+}
+
+/// (De)serialize a big-endian `U16` as a plain number.
+pub mod be_u16 {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    type U16 = zerocopy::byteorder::U16<crate::layout::Endianness>;
+
+    pub fn serialize<S>(v: &U16, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u16(v.get())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<U16, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        u16::deserialize(deserializer).map(U16::new)
+    }
+}
+
+pub fn ilda() -> [u8; 4] {
+    layout::Header::ILDA
+}
+
+pub fn is_zero<T>(v: &T) -> bool
+where
+    T: Default + PartialEq,
+{
+    *v == T::default()
+}
+
+// The status byte as booleans, retaining any bits undefined by the specification.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct StatusRepr {
+    last_point: bool,
+    blanking: bool,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    undefined: u8,
+}
+
+impl Serialize for layout::Format {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match FORMATS.iter().position(|&f| f == *self) {
+            Some(i) => serializer.serialize_str(FORMAT_NAMES[i]),
+            None => serializer.serialize_u8(self.0),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for layout::Format {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct FormatVisitor;
+        impl<'de> Visitor<'de> for FormatVisitor {
+            type Value = layout::Format;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a format name or format code")
+            }
+            fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                FORMAT_NAMES
+                    .iter()
+                    .position(|&name| name == s)
+                    .map(|i| FORMATS[i])
+                    .ok_or_else(|| E::unknown_variant(s, FORMAT_NAMES))
+            }
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                if v > u8::MAX as u64 {
+                    return Err(E::invalid_value(de::Unexpected::Unsigned(v), &self));
+                }
+                Ok(layout::Format(v as u8))
+            }
+            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                if v < 0 {
+                    return Err(E::invalid_value(de::Unexpected::Signed(v), &self));
+                }
+                self.visit_u64(v as u64)
+            }
+        }
+        deserializer.deserialize_any(FormatVisitor)
+    }
+}
+
+impl Serialize for layout::Name {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.as_str() {
+            Ok(s) => serializer.serialize_str(s),
+            Err(_) => serializer.serialize_bytes(&self.0),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for layout::Name {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct NameVisitor;
+        impl<'de> Visitor<'de> for NameVisitor {
+            type Value = layout::Name;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a string or sequence of at most 8 bytes")
+            }
+            fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                self.visit_bytes(s.as_bytes())
+            }
+            fn visit_bytes<E>(self, bytes: &[u8]) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                let mut name = [0u8; 8];
+                if bytes.len() > name.len() {
+                    return Err(E::invalid_length(bytes.len(), &self));
+                }
+                name[..bytes.len()].copy_from_slice(bytes);
+                Ok(layout::Name(name))
+            }
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let mut name = [0u8; 8];
+                let mut len = 0;
+                while let Some(byte) = seq.next_element::<u8>()? {
+                    if len == name.len() {
+                        return Err(de::Error::invalid_length(len + 1, &self));
+                    }
+                    name[len] = byte;
+                    len += 1;
+                }
+                Ok(layout::Name(name))
+            }
+        }
+        deserializer.deserialize_any(NameVisitor)
+    }
+}
+
+impl Serialize for layout::Status {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let repr = StatusRepr {
+            last_point: self.is_last_point(),
+            blanking: self.is_blanking(),
+            undefined: self.bits() & !layout::Status::all().bits(),
+        };
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for layout::Status {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let repr = StatusRepr::deserialize(deserializer)?;
+        let mut bits = repr.undefined & !layout::Status::all().bits();
+        if repr.last_point {
+            bits |= layout::Status::LAST_POINT.bits();
+        }
+        if repr.blanking {
+            bits |= layout::Status::BLANKING.bits();
+        }
+        // Map the byte directly in order to retain undefined bits.
+        let bytes = [bits];
+        let status = zerocopy::LayoutVerified::<_, layout::Status>::new(&bytes[..])
+            .map(zerocopy::LayoutVerified::into_ref)
+            .expect("a single byte must match the layout of `Status`");
+        Ok(*status)
+    }
+}
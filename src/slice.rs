@@ -0,0 +1,204 @@
+//! Items related to reading IDTF directly from a slice of bytes.
+
+use crate::{layout, record_size, Error, Position};
+use std::mem;
+
+/// Reads a sequence of IDTF sections directly from a slice of bytes.
+///
+/// Unlike the **SectionReader**, no bytes are copied. Each yielded **SliceSection** refers
+/// directly to the header and records within the slice, allowing for a whole section to be
+/// accessed at once.
+///
+/// This is particularly useful in combination with memory-mapped files.
+#[derive(Clone, Debug)]
+pub struct SliceSectionReader<'a> {
+    bytes: &'a [u8],
+    position: Position,
+    end_of_file: Option<&'a layout::Header>,
+}
+
+/// Contains a verified **Header** and the records of a section read from a slice of bytes.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SliceSection<'a> {
+    pub header: &'a layout::Header,
+    pub records: SliceRecords<'a>,
+}
+
+/// The records of a section determined via the header's `format` field.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum SliceRecords<'a> {
+    Coords3dIndexedColor(&'a [layout::Coords3dIndexedColor]),
+    Coords2dIndexedColor(&'a [layout::Coords2dIndexedColor]),
+    ColorPalette(&'a [layout::ColorPalette]),
+    Coords3dTrueColor(&'a [layout::Coords3dTrueColor]),
+    Coords2dTrueColor(&'a [layout::Coords2dTrueColor]),
+}
+
+impl<'a> SliceSectionReader<'a> {
+    /// Read ILDA IDTF sections from the given slice of bytes.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        let position = Position::default();
+        let end_of_file = None;
+        SliceSectionReader {
+            bytes,
+            position,
+            end_of_file,
+        }
+    }
+
+    /// The position of the next section header.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// The end of file header, if one has been read.
+    ///
+    /// See **SectionReader::end_of_file**.
+    pub fn end_of_file(&self) -> Option<&'a layout::Header> {
+        self.end_of_file
+    }
+
+    /// Read the next **SliceSection**.
+    ///
+    /// Returns `None` once the end of file header or the end of the slice is reached. A header
+    /// truncated by the end of the slice is treated as the end of the slice.
+    pub fn read_next(&mut self) -> Result<Option<SliceSection<'a>>, Error> {
+        if self.end_of_file.is_some() {
+            return Ok(None);
+        }
+
+        // Verify the header layout.
+        let (header, rest) =
+            match zerocopy::LayoutVerified::<_, layout::Header>::new_from_prefix(self.bytes) {
+                Some((header, rest)) => (header.into_ref(), rest),
+                None => return Ok(None),
+            };
+        let position = self.position;
+
+        // Validate header by ascii "ILDA" and a known format.
+        if header.ilda != layout::Header::ILDA {
+            return Err(Error::BadMagic {
+                position,
+                found: header.ilda,
+            });
+        }
+        let record_size = match record_size(header.format) {
+            Some(size) => size,
+            None => {
+                return Err(Error::UnknownFormat {
+                    position,
+                    format: header.format.0,
+                })
+            }
+        };
+
+        // Stop at the end of file header.
+        let len = header.num_records.get();
+        if len == 0 {
+            self.end_of_file = Some(header);
+            self.bytes = rest;
+            return Ok(None);
+        }
+
+        // Verify that the whole section is present.
+        let records_len = len as usize * record_size;
+        if rest.len() < records_len {
+            let header_size = mem::size_of::<layout::Header>() as u64;
+            let got = rest.len() / record_size;
+            let offset = position.offset + header_size + (got * record_size) as u64;
+            return Err(Error::TruncatedSection {
+                position: Position {
+                    section: position.section,
+                    offset,
+                },
+                expected: len,
+                got: got as u16,
+            });
+        }
+        let (records, rest) = rest.split_at(records_len);
+        self.bytes = rest;
+        self.position.section += 1;
+        self.position.offset += (mem::size_of::<layout::Header>() + records_len) as u64;
+
+        // Map the records.
+        let records = match header.format {
+            layout::Format::COORDS_3D_INDEXED_COLOR => {
+                SliceRecords::Coords3dIndexedColor(records_slice(records))
+            }
+            layout::Format::COORDS_2D_INDEXED_COLOR => {
+                SliceRecords::Coords2dIndexedColor(records_slice(records))
+            }
+            layout::Format::COLOR_PALETTE => SliceRecords::ColorPalette(records_slice(records)),
+            layout::Format::COORDS_3D_TRUE_COLOR => {
+                SliceRecords::Coords3dTrueColor(records_slice(records))
+            }
+            layout::Format::COORDS_2D_TRUE_COLOR => {
+                SliceRecords::Coords2dTrueColor(records_slice(records))
+            }
+            _ => unreachable!("format verified by `record_size`"),
+        };
+
+        Ok(Some(SliceSection { header, records }))
+    }
+}
+
+impl<'a> SliceRecords<'a> {
+    /// The number of records within the section.
+    pub fn len(&self) -> usize {
+        match *self {
+            SliceRecords::Coords3dIndexedColor(r) => r.len(),
+            SliceRecords::Coords2dIndexedColor(r) => r.len(),
+            SliceRecords::ColorPalette(r) => r.len(),
+            SliceRecords::Coords3dTrueColor(r) => r.len(),
+            SliceRecords::Coords2dTrueColor(r) => r.len(),
+        }
+    }
+
+    /// Whether or not the section contains no records.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a> Iterator for SliceSectionReader<'a> {
+    type Item = Result<SliceSection<'a>, Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.read_next() {
+            Ok(Some(section)) => Some(Ok(section)),
+            Ok(None) => None,
+            Err(err) => {
+                // Avoid yielding the same error indefinitely.
+                self.bytes = &[];
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+fn records_slice<T>(bytes: &[u8]) -> &[T]
+where
+    T: zerocopy::FromBytes,
+{
+    zerocopy::LayoutVerified::new_slice(bytes)
+        .map(zerocopy::LayoutVerified::into_slice)
+        .expect("bytes must match the layout of the records")
+}
+
+/// Memory-map the file at the given path.
+///
+/// The returned map dereferences to the file's bytes and may be read via a
+/// **SliceSectionReader** without copying any bytes.
+///
+/// # Safety
+///
+/// The file must not be modified or truncated by this or any other process while it is mapped.
+/// Doing so is undefined behaviour, as the bytes behind the returned slice may change or become
+/// inaccessible while they are being read.
+#[cfg(feature = "memmap")]
+pub unsafe fn open_mmap<P>(path: P) -> std::io::Result<impl std::ops::Deref<Target = [u8]>>
+where
+    P: AsRef<std::path::Path>,
+{
+    let file = std::fs::File::open(path)?;
+    memmap::Mmap::map(&file)
+}
@@ -0,0 +1,958 @@
+//! Items related to converting frames to and from SVG.
+//!
+//! ## Export
+//!
+//! Frames may be exported to SVG for previewing content without a laser via **SvgOptions**.
+//!
+//! Each run of consecutive unblanked points of the same color is drawn as a single polyline. As
+//! the beam travels *to* each point, a segment takes the color of the point at which it ends and
+//! segments ending at blanked points are not drawn, splitting the path. A lit first point is drawn
+//! as a dot.
+//!
+//! IDTF coordinates span the range of `i16` with y increasing upwards. These are mapped onto an
+//! SVG viewport of the same extent with y flipped to increase downwards. The z coordinate of 3D
+//! points is ignored.
+//!
+//! ## Import
+//!
+//! SVG documents may be imported as a frame of **Coords2dTrueColor** points via
+//! **ImportOptions**. The `path`, `line`, `polyline` and `polygon` elements are supported, with
+//! path data consisting of lines, cubic and quadratic Béziers and elliptical arcs. Points are
+//! placed along each path at a configurable spacing and blanked travel moves are inserted between
+//! subpaths. Points take the color of their element's stroke, which may be inherited from
+//! enclosing groups. Transforms, CSS style sheets and filled shapes are not supported.
+
+use crate::{animation, layout};
+use std::{f32::consts::PI, fmt, fmt::Write};
+
+type I16 = zerocopy::byteorder::I16<layout::Endianness>;
+
+/// Options for exporting frames to SVG.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvgOptions {
+    size: u32,
+    stroke_width: f32,
+    background: Option<layout::Color>,
+}
+
+// The minimal information required to draw a point.
+#[derive(Copy, Clone)]
+struct SvgPoint {
+    x: i16,
+    y: i16,
+    color: layout::Color,
+    blanking: bool,
+}
+
+impl SvgOptions {
+    /// The default options: a 512x512 pixel image on a black background with strokes 1/256th of
+    /// the coordinate range wide.
+    pub fn new() -> Self {
+        SvgOptions {
+            size: 512,
+            stroke_width: 256.0,
+            background: Some(layout::Color::BLACK),
+        }
+    }
+
+    /// The width and height of the image in pixels.
+    pub fn size(&mut self, size: u32) -> &mut Self {
+        self.size = size;
+        self
+    }
+
+    /// The width of strokes in IDTF coordinate units.
+    pub fn stroke_width(&mut self, width: f32) -> &mut Self {
+        self.stroke_width = width;
+        self
+    }
+
+    /// The background color, or `None` for a transparent background.
+    pub fn background(&mut self, color: Option<layout::Color>) -> &mut Self {
+        self.background = color;
+        self
+    }
+
+    /// Export the frame as an SVG document.
+    pub fn frame(&self, frame: &animation::Frame) -> String {
+        let points = frame.points.iter().map(|p| SvgPoint {
+            x: p.x,
+            y: p.y,
+            color: p.color,
+            blanking: p.blanking,
+        });
+        self.svg(points)
+    }
+
+    /// Export the given frame points as an SVG document.
+    ///
+    /// Indexed colors are resolved via the given palette.
+    pub fn points<P>(&self, points: &[P], palette: &[layout::Color]) -> String
+    where
+        P: layout::PointLayout,
+    {
+        let points = points.iter().map(|p| SvgPoint {
+            x: p.x(),
+            y: p.y(),
+            color: p.color(palette),
+            blanking: p.status().is_blanking(),
+        });
+        self.svg(points)
+    }
+
+    fn svg<I>(&self, points: I) -> String
+    where
+        I: IntoIterator<Item = SvgPoint>,
+    {
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" \
+             viewBox=\"-32768 -32768 65536 65536\">",
+            self.size,
+        )
+        .unwrap();
+        if let Some(color) = self.background {
+            writeln!(
+                svg,
+                "<rect x=\"-32768\" y=\"-32768\" width=\"65536\" height=\"65536\" fill=\"{}\"/>",
+                hex(color),
+            )
+            .unwrap();
+        }
+        writeln!(
+            svg,
+            "<g fill=\"none\" stroke-width=\"{}\" stroke-linecap=\"round\" \
+             stroke-linejoin=\"round\">",
+            self.stroke_width,
+        )
+        .unwrap();
+
+        // Accumulate polylines of lit segments sharing the same color.
+        let mut polyline: Option<(layout::Color, Vec<(i16, i16)>)> = None;
+        let mut prev: Option<SvgPoint> = None;
+        for p in points {
+            if p.blanking {
+                write_polyline(&mut svg, polyline.take());
+            } else {
+                match polyline {
+                    Some((color, ref mut coords)) if color == p.color => coords.push((p.x, p.y)),
+                    _ => {
+                        write_polyline(&mut svg, polyline.take());
+                        // Begin from the previous point, or draw a dot for the first point.
+                        let start = prev.map(|q| (q.x, q.y)).unwrap_or((p.x, p.y));
+                        polyline = Some((p.color, vec![start, (p.x, p.y)]));
+                    }
+                }
+            }
+            prev = Some(p);
+        }
+        write_polyline(&mut svg, polyline);
+
+        svg.push_str("</g>\n</svg>\n");
+        svg
+    }
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Export the frame as an SVG document with the default **SvgOptions**.
+pub fn frame_to_svg(frame: &animation::Frame) -> String {
+    SvgOptions::new().frame(frame)
+}
+
+fn write_polyline(svg: &mut String, polyline: Option<(layout::Color, Vec<(i16, i16)>)>) {
+    let (color, coords) = match polyline {
+        None => return,
+        Some(polyline) => polyline,
+    };
+    write!(svg, "<polyline stroke=\"{}\" points=\"", hex(color)).unwrap();
+    for (i, &(x, y)) in coords.iter().enumerate() {
+        if i > 0 {
+            svg.push(' ');
+        }
+        // Flip y so that it increases downwards.
+        write!(svg, "{},{}", x, -(y as i32)).unwrap();
+    }
+    svg.push_str("\"/>\n");
+}
+
+fn hex(color: layout::Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.red, color.green, color.blue)
+}
+
+/// Options for importing SVG documents as frames.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportOptions {
+    point_spacing: f32,
+    blank_points: usize,
+    default_color: layout::Color,
+}
+
+/// An error produced while importing an SVG document.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ImportError {
+    /// A description of the error.
+    pub message: String,
+}
+
+// A segment of a subpath in SVG user units.
+#[derive(Copy, Clone, Debug)]
+enum Segment {
+    Line([f32; 2], [f32; 2]),
+    Quad([f32; 2], [f32; 2], [f32; 2]),
+    Cubic([f32; 2], [f32; 2], [f32; 2], [f32; 2]),
+    Arc {
+        center: [f32; 2],
+        radii: [f32; 2],
+        rotation: f32,
+        start: f32,
+        sweep: f32,
+    },
+}
+
+// A continuous subpath and the color with which it is stroked.
+struct Subpath {
+    color: layout::Color,
+    start: [f32; 2],
+    // The position at which the last segment ends.
+    end: [f32; 2],
+    segments: Vec<Segment>,
+}
+
+impl ImportOptions {
+    /// The default options: points spaced at most 512 coordinate units apart, 8 blanked points
+    /// per travel move and white for elements without a stroke color.
+    pub fn new() -> Self {
+        ImportOptions {
+            point_spacing: 512.0,
+            blank_points: 8,
+            default_color: layout::Color {
+                red: 255,
+                green: 255,
+                blue: 255,
+            },
+        }
+    }
+
+    /// The maximum distance between consecutive points along a path in IDTF coordinate units.
+    pub fn point_spacing(&mut self, spacing: f32) -> &mut Self {
+        self.point_spacing = spacing;
+        self
+    }
+
+    /// The number of blanked points making up each travel move between subpaths, including the
+    /// points at either end. At least 2 are always used.
+    pub fn blank_points(&mut self, n: usize) -> &mut Self {
+        self.blank_points = n;
+        self
+    }
+
+    /// The color of elements that do not specify a stroke.
+    pub fn default_color(&mut self, color: layout::Color) -> &mut Self {
+        self.default_color = color;
+        self
+    }
+
+    /// Import the SVG document as a frame.
+    ///
+    /// The document's `viewBox`, or its `width` and `height` if there is no `viewBox`, is scaled
+    /// uniformly to fit the IDTF coordinate range. Otherwise the bounds of the geometry are used.
+    /// The last point has the `LAST_POINT` status bit set.
+    ///
+    /// Returns an empty frame if the document contains no supported elements.
+    pub fn import(&self, svg: &str) -> Result<Vec<layout::Coords2dTrueColor>, ImportError> {
+        let (view_box, subpaths) = parse_document(svg, self.default_color)?;
+        let view_box = match view_box.or_else(|| bounds(&subpaths)) {
+            None => return Ok(vec![]),
+            Some(view_box) => view_box,
+        };
+
+        // Map the view box onto the coordinate range, centered and flipping y.
+        let [min_x, min_y, w, h] = view_box;
+        let max = i16::MAX as f32;
+        let scale = if w.max(h) > 0.0 {
+            2.0 * max / w.max(h)
+        } else {
+            1.0
+        };
+        let (cx, cy) = (min_x + w / 2.0, min_y + h / 2.0);
+        let map = |[x, y]: [f32; 2]| [(x - cx) * scale, (cy - y) * scale];
+
+        let spacing = self.point_spacing.max(1.0);
+        let mut points = vec![];
+        // Check the number of points before generating them, as it is otherwise unbounded.
+        let reserve = |points: &Vec<_>, n: usize| {
+            let total = points.len().saturating_add(n);
+            if total > u16::MAX as usize {
+                let message = format!("{} points exceeds the maximum of 65535", total);
+                return Err(ImportError { message });
+            }
+            Ok(())
+        };
+        let mut prev: Option<[f32; 2]> = None;
+        for subpath in &subpaths {
+            // Travel to the start of the subpath while blanked.
+            let start = map(subpath.start);
+            let from = prev.unwrap_or(start);
+            let n = self.blank_points.max(2) - 1;
+            reserve(&points, n.saturating_add(2))?;
+            for i in 0..=n {
+                let t = i as f32 / n as f32;
+                points.push(point(
+                    lerp(from, start, t),
+                    layout::Status::BLANKING,
+                    layout::Color::BLACK,
+                ));
+            }
+
+            // Trace the subpath while lit.
+            let lit = layout::Status::empty();
+            points.push(point(start, lit, subpath.color));
+            let mut end = start;
+            for segment in &subpath.segments {
+                let length = segment_length(segment, &map);
+                let n = ((length / spacing).ceil() as usize).max(1);
+                reserve(&points, n)?;
+                for i in 1..=n {
+                    end = map(segment_point(segment, i as f32 / n as f32));
+                    points.push(point(end, lit, subpath.color));
+                }
+            }
+            prev = Some(end);
+        }
+
+        if let Some(last) = points.last_mut() {
+            last.status |= layout::Status::LAST_POINT;
+        }
+        Ok(points)
+    }
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Import the SVG document as a frame with the default **ImportOptions**.
+pub fn svg_to_frame(svg: &str) -> Result<Vec<layout::Coords2dTrueColor>, ImportError> {
+    ImportOptions::new().import(svg)
+}
+
+fn point(p: [f32; 2], status: layout::Status, color: layout::Color) -> layout::Coords2dTrueColor {
+    layout::Coords2dTrueColor {
+        coords: layout::Coords2d {
+            x: I16::new(crate::saturate(p[0])),
+            y: I16::new(crate::saturate(p[1])),
+        },
+        status,
+        color,
+    }
+}
+
+fn lerp(a: [f32; 2], b: [f32; 2], t: f32) -> [f32; 2] {
+    [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t]
+}
+
+fn distance(a: [f32; 2], b: [f32; 2]) -> f32 {
+    (b[0] - a[0]).hypot(b[1] - a[1])
+}
+
+// The point along the segment at `t` within the range 0 to 1.
+fn segment_point(segment: &Segment, t: f32) -> [f32; 2] {
+    let mt = 1.0 - t;
+    match *segment {
+        Segment::Line(a, b) => lerp(a, b, t),
+        Segment::Quad(a, b, c) => {
+            let f = |i: usize| mt * mt * a[i] + 2.0 * mt * t * b[i] + t * t * c[i];
+            [f(0), f(1)]
+        }
+        Segment::Cubic(a, b, c, d) => {
+            let f = |i: usize| {
+                mt * mt * mt * a[i]
+                    + 3.0 * mt * mt * t * b[i]
+                    + 3.0 * mt * t * t * c[i]
+                    + t * t * t * d[i]
+            };
+            [f(0), f(1)]
+        }
+        Segment::Arc {
+            center,
+            radii,
+            rotation,
+            start,
+            sweep,
+        } => {
+            let angle = start + sweep * t;
+            let (x, y) = (radii[0] * angle.cos(), radii[1] * angle.sin());
+            let (sin, cos) = rotation.sin_cos();
+            [center[0] + cos * x - sin * y, center[1] + sin * x + cos * y]
+        }
+    }
+}
+
+// Approximate the length of the mapped segment by sampling.
+fn segment_length<F>(segment: &Segment, map: &F) -> f32
+where
+    F: Fn([f32; 2]) -> [f32; 2],
+{
+    const SAMPLES: usize = 16;
+    if let Segment::Line(a, b) = *segment {
+        return distance(map(a), map(b));
+    }
+    let mut length = 0.0;
+    let mut prev = map(segment_point(segment, 0.0));
+    for i in 1..=SAMPLES {
+        let p = map(segment_point(segment, i as f32 / SAMPLES as f32));
+        length += distance(prev, p);
+        prev = p;
+    }
+    length
+}
+
+// The bounds of all segment end and control points as `[min_x, min_y, width, height]`.
+fn bounds(subpaths: &[Subpath]) -> Option<[f32; 4]> {
+    let mut min = [f32::INFINITY; 2];
+    let mut max = [f32::NEG_INFINITY; 2];
+    let mut include = |p: [f32; 2]| {
+        for i in 0..2 {
+            min[i] = min[i].min(p[i]);
+            max[i] = max[i].max(p[i]);
+        }
+    };
+    for subpath in subpaths {
+        include(subpath.start);
+        for segment in &subpath.segments {
+            for i in 0..=16 {
+                include(segment_point(segment, i as f32 / 16.0));
+            }
+        }
+    }
+    if subpaths.is_empty() {
+        return None;
+    }
+    Some([min[0], min[1], max[0] - min[0], max[1] - min[1]])
+}
+
+// Parse the view box and all supported elements of the document.
+fn parse_document(
+    svg: &str,
+    default_color: layout::Color,
+) -> Result<(Option<[f32; 4]>, Vec<Subpath>), ImportError> {
+    let mut view_box = None;
+    let mut subpaths = vec![];
+    // The stroke inherited from enclosing groups. `None` represents `stroke="none"`.
+    let mut strokes: Vec<Option<layout::Color>> = vec![Some(default_color)];
+    let mut rest = svg;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        // Skip comments, declarations and processing instructions.
+        let close = if rest.starts_with("!--") {
+            "-->"
+        } else if rest.starts_with('!') || rest.starts_with('?') {
+            ">"
+        } else {
+            ""
+        };
+        if !close.is_empty() {
+            let end = rest
+                .find(close)
+                .ok_or_else(|| import_error("unterminated tag"))?;
+            rest = &rest[end + close.len()..];
+            continue;
+        }
+        let end = tag_end(rest).ok_or_else(|| import_error("unterminated tag"))?;
+        let tag = &rest[..end];
+        rest = &rest[end + 1..];
+
+        if let Some(name) = tag.strip_prefix('/') {
+            if name.trim() == "g" && strokes.len() > 1 {
+                strokes.pop();
+            }
+            continue;
+        }
+        let self_closing = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+        let (name, attrs) = (&tag[..name_end], parse_attributes(&tag[name_end..])?);
+        let attr = |key: &str| attrs.iter().find(|&&(k, _)| k == key).map(|&(_, v)| v);
+        let inherited = *strokes.last().expect("the default stroke is never popped");
+        let stroke = match stroke(&attrs)? {
+            Some(stroke) => stroke,
+            None => inherited,
+        };
+
+        let mut path = String::new();
+        let d = match name {
+            "svg" => {
+                if view_box.is_none() {
+                    view_box = parse_view_box(attr("viewBox"), attr("width"), attr("height"))?;
+                }
+                continue;
+            }
+            "g" => {
+                if !self_closing {
+                    strokes.push(stroke);
+                }
+                continue;
+            }
+            "path" => attr("d").unwrap_or(""),
+            "line" => {
+                let num = |key| attr(key).map(parse_length).unwrap_or(Ok(0.0));
+                let (x1, y1, x2, y2) = (num("x1")?, num("y1")?, num("x2")?, num("y2")?);
+                write!(path, "M{} {} L{} {}", x1, y1, x2, y2).unwrap();
+                &path
+            }
+            "polyline" | "polygon" => {
+                path.push('M');
+                path.push_str(attr("points").unwrap_or(""));
+                if name == "polygon" {
+                    path.push('Z');
+                }
+                &path
+            }
+            _ => continue,
+        };
+        if let Some(color) = stroke {
+            parse_path(d, color, &mut subpaths)?;
+        }
+    }
+    // Subpaths consisting of a lone move draw nothing.
+    subpaths.retain(|subpath| !subpath.segments.is_empty());
+    Ok((view_box, subpaths))
+}
+
+// The index of the `>` ending the tag, ignoring any within quoted attribute values.
+fn tag_end(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '>') => return Some(i),
+            _ => (),
+        }
+    }
+    None
+}
+
+fn parse_attributes(s: &str) -> Result<Vec<(&str, &str)>, ImportError> {
+    let mut attrs = vec![];
+    let mut rest = s.trim_start();
+    while !rest.is_empty() {
+        let eq = rest
+            .find('=')
+            .ok_or_else(|| import_error(format!("expected an attribute value in `{}`", s)))?;
+        let key = rest[..eq].trim();
+        let value = rest[eq + 1..].trim_start();
+        let quote = value
+            .chars()
+            .next()
+            .filter(|&c| c == '"' || c == '\'')
+            .ok_or_else(|| import_error(format!("expected a quoted value for `{}`", key)))?;
+        let end = value[1..]
+            .find(quote)
+            .ok_or_else(|| import_error(format!("unterminated value for `{}`", key)))?;
+        attrs.push((key, &value[1..end + 1]));
+        rest = value[end + 2..].trim_start();
+    }
+    Ok(attrs)
+}
+
+// The stroke color specified by the element's `stroke` attribute or `style`, if any.
+fn stroke(attrs: &[(&str, &str)]) -> Result<Option<Option<layout::Color>>, ImportError> {
+    let style = attrs
+        .iter()
+        .filter(|&&(k, _)| k == "style")
+        .flat_map(|&(_, v)| v.split(';'))
+        .filter_map(|decl| {
+            let colon = decl.find(':')?;
+            let (key, value) = (decl[..colon].trim(), decl[colon + 1..].trim());
+            if key == "stroke" {
+                Some(value)
+            } else {
+                None
+            }
+        })
+        .next_back();
+    let value = style.or_else(|| attrs.iter().find(|&&(k, _)| k == "stroke").map(|&(_, v)| v));
+    match value.map(str::trim) {
+        None | Some("inherit") => Ok(None),
+        Some("none") => Ok(Some(None)),
+        Some(color) => parse_color(color).map(|c| Some(Some(c))),
+    }
+}
+
+fn parse_color(s: &str) -> Result<layout::Color, ImportError> {
+    let rgb = |red, green, blue| layout::Color { red, green, blue };
+    let color = match s {
+        "black" => rgb(0, 0, 0),
+        "white" => rgb(255, 255, 255),
+        "red" => rgb(255, 0, 0),
+        "lime" => rgb(0, 255, 0),
+        "green" => rgb(0, 128, 0),
+        "blue" => rgb(0, 0, 255),
+        "yellow" => rgb(255, 255, 0),
+        "cyan" | "aqua" => rgb(0, 255, 255),
+        "magenta" | "fuchsia" => rgb(255, 0, 255),
+        s if s.starts_with('#')
+            && (s.len() == 4 || s.len() == 7)
+            && s.bytes().skip(1).all(|b| b.is_ascii_hexdigit()) =>
+        {
+            let hex = &s[1..];
+            let digit = |i: usize, n: usize| {
+                u8::from_str_radix(&hex[i * n..i * n + n], 16)
+                    .map(|v| if n == 1 { v * 17 } else { v })
+                    .map_err(|_| import_error(format!("invalid color `{}`", s)))
+            };
+            let n = hex.len() / 3;
+            rgb(digit(0, n)?, digit(1, n)?, digit(2, n)?)
+        }
+        s => return Err(import_error(format!("unsupported color `{}`", s))),
+    };
+    Ok(color)
+}
+
+fn parse_view_box(
+    view_box: Option<&str>,
+    width: Option<&str>,
+    height: Option<&str>,
+) -> Result<Option<[f32; 4]>, ImportError> {
+    if let Some(view_box) = view_box {
+        let values = parse_numbers(view_box)?;
+        if values.len() != 4 {
+            return Err(import_error(format!("invalid viewBox `{}`", view_box)));
+        }
+        return Ok(Some([values[0], values[1], values[2], values[3]]));
+    }
+    match (width, height) {
+        (Some(w), Some(h)) => Ok(Some([0.0, 0.0, parse_length(w)?, parse_length(h)?])),
+        _ => Ok(None),
+    }
+}
+
+// Parse a length, ignoring any `px` unit.
+fn parse_length(s: &str) -> Result<f32, ImportError> {
+    let s = s.trim().trim_end_matches("px");
+    s.parse()
+        .map_err(|_| import_error(format!("invalid length `{}`", s)))
+}
+
+fn parse_numbers(s: &str) -> Result<Vec<f32>, ImportError> {
+    let mut parser = PathParser {
+        s: s.as_bytes(),
+        i: 0,
+    };
+    let mut values = vec![];
+    while parser.skip_separators() {
+        values.push(parser.number()?);
+    }
+    Ok(values)
+}
+
+// Parse the path data, appending each subpath.
+fn parse_path(
+    d: &str,
+    color: layout::Color,
+    subpaths: &mut Vec<Subpath>,
+) -> Result<(), ImportError> {
+    let mut p = PathParser {
+        s: d.as_bytes(),
+        i: 0,
+    };
+    let mut current = [0.0f32; 2];
+    let mut start = [0.0f32; 2];
+    let mut command = None;
+    // The second control point of the previous curve, for smooth curve commands.
+    let mut prev_cubic: Option<[f32; 2]> = None;
+    let mut prev_quad: Option<[f32; 2]> = None;
+    while p.skip_separators() {
+        let c = p.s[p.i];
+        if c.is_ascii_alphabetic() {
+            p.i += 1;
+            command = Some(c);
+        } else if command.is_none() {
+            return Err(import_error(format!(
+                "path data must begin with a command `{}`",
+                d
+            )));
+        }
+        let c = command.expect("command must be set");
+        let relative = c.is_ascii_lowercase();
+        let offset = if relative { current } else { [0.0; 2] };
+        let coord = |p: &mut PathParser| -> Result<[f32; 2], ImportError> {
+            Ok([p.number()? + offset[0], p.number()? + offset[1]])
+        };
+        let (mut cubic, mut quad) = (None, None);
+        match c.to_ascii_uppercase() {
+            b'M' => {
+                current = coord(&mut p)?;
+                start = current;
+                subpaths.push(Subpath {
+                    color,
+                    start,
+                    end: start,
+                    segments: vec![],
+                });
+                // Subsequent coordinate pairs are implicit line commands.
+                command = Some(if relative { b'l' } else { b'L' });
+            }
+            b'L' => {
+                let to = coord(&mut p)?;
+                push_segment(subpaths, color, current, to, Segment::Line(current, to));
+                current = to;
+            }
+            b'H' => {
+                let x = p.number()? + offset[0];
+                let to = [x, current[1]];
+                push_segment(subpaths, color, current, to, Segment::Line(current, to));
+                current = to;
+            }
+            b'V' => {
+                let y = p.number()? + offset[1];
+                let to = [current[0], y];
+                push_segment(subpaths, color, current, to, Segment::Line(current, to));
+                current = to;
+            }
+            b'C' | b'S' => {
+                let c1 = if c.eq_ignore_ascii_case(&b'C') {
+                    coord(&mut p)?
+                } else {
+                    reflect(prev_cubic, current)
+                };
+                let c2 = coord(&mut p)?;
+                let to = coord(&mut p)?;
+                push_segment(
+                    subpaths,
+                    color,
+                    current,
+                    to,
+                    Segment::Cubic(current, c1, c2, to),
+                );
+                cubic = Some(c2);
+                current = to;
+            }
+            b'Q' | b'T' => {
+                let c1 = if c.eq_ignore_ascii_case(&b'Q') {
+                    coord(&mut p)?
+                } else {
+                    reflect(prev_quad, current)
+                };
+                let to = coord(&mut p)?;
+                push_segment(subpaths, color, current, to, Segment::Quad(current, c1, to));
+                quad = Some(c1);
+                current = to;
+            }
+            b'A' => {
+                let (rx, ry, rotation) = (p.number()?, p.number()?, p.number()?);
+                let (large_arc, sweep) = (p.flag()?, p.flag()?);
+                let to = coord(&mut p)?;
+                let segment = arc(current, [rx, ry], rotation, large_arc, sweep, to);
+                push_segment(subpaths, color, current, to, segment);
+                current = to;
+            }
+            b'Z' => {
+                if current != start {
+                    push_segment(
+                        subpaths,
+                        color,
+                        current,
+                        start,
+                        Segment::Line(current, start),
+                    );
+                }
+                current = start;
+                command = None;
+            }
+            _ => {
+                return Err(import_error(format!(
+                    "unknown path command `{}`",
+                    c as char
+                )))
+            }
+        }
+        prev_cubic = cubic;
+        prev_quad = quad;
+    }
+    Ok(())
+}
+
+// Append the segment from `from` to `to` to the current subpath, beginning a new subpath unless
+// the current subpath has the same color and ends at `from`.
+fn push_segment(
+    subpaths: &mut Vec<Subpath>,
+    color: layout::Color,
+    from: [f32; 2],
+    to: [f32; 2],
+    s: Segment,
+) {
+    match subpaths.last_mut() {
+        Some(subpath) if subpath.color == color && subpath.end == from => {
+            subpath.segments.push(s);
+            subpath.end = to;
+        }
+        _ => subpaths.push(Subpath {
+            color,
+            start: from,
+            end: to,
+            segments: vec![s],
+        }),
+    }
+}
+
+// Reflect the previous control point about the current point.
+fn reflect(control: Option<[f32; 2]>, current: [f32; 2]) -> [f32; 2] {
+    match control {
+        None => current,
+        Some(c) => [2.0 * current[0] - c[0], 2.0 * current[1] - c[1]],
+    }
+}
+
+// Convert an arc from endpoint to center parameterization, as per the SVG implementation notes.
+fn arc(
+    from: [f32; 2],
+    radii: [f32; 2],
+    rotation_degrees: f32,
+    large_arc: bool,
+    sweep: bool,
+    to: [f32; 2],
+) -> Segment {
+    let (mut rx, mut ry) = (radii[0].abs(), radii[1].abs());
+    if rx == 0.0 || ry == 0.0 || from == to {
+        return Segment::Line(from, to);
+    }
+    let rotation = rotation_degrees.to_radians();
+    let (sin, cos) = rotation.sin_cos();
+    let (dx, dy) = ((from[0] - to[0]) / 2.0, (from[1] - to[1]) / 2.0);
+    let x1 = cos * dx + sin * dy;
+    let y1 = -sin * dx + cos * dy;
+
+    // Scale up the radii if they are too small to span the endpoints.
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let num = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let den = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut coef = (num / den).max(0.0).sqrt();
+    if large_arc == sweep {
+        coef = -coef;
+    }
+    let cx1 = coef * rx * y1 / ry;
+    let cy1 = -coef * ry * x1 / rx;
+    let center = [
+        cos * cx1 - sin * cy1 + (from[0] + to[0]) / 2.0,
+        sin * cx1 + cos * cy1 + (from[1] + to[1]) / 2.0,
+    ];
+
+    let angle = |ux: f32, uy: f32| uy.atan2(ux);
+    let start = angle((x1 - cx1) / rx, (y1 - cy1) / ry);
+    let end = angle((-x1 - cx1) / rx, (-y1 - cy1) / ry);
+    let mut sweep_angle = end - start;
+    if sweep && sweep_angle < 0.0 {
+        sweep_angle += 2.0 * PI;
+    } else if !sweep && sweep_angle > 0.0 {
+        sweep_angle -= 2.0 * PI;
+    }
+    Segment::Arc {
+        center,
+        radii: [rx, ry],
+        rotation,
+        start,
+        sweep: sweep_angle,
+    }
+}
+
+// A cursor over path data or other lists of numbers.
+struct PathParser<'a> {
+    s: &'a [u8],
+    i: usize,
+}
+
+impl<'a> PathParser<'a> {
+    // Skip whitespace and commas, returning whether or not any data remains.
+    fn skip_separators(&mut self) -> bool {
+        while self.i < self.s.len()
+            && (self.s[self.i].is_ascii_whitespace() || self.s[self.i] == b',')
+        {
+            self.i += 1;
+        }
+        self.i < self.s.len()
+    }
+
+    fn number(&mut self) -> Result<f32, ImportError> {
+        self.skip_separators();
+        let start = self.i;
+        let s = self.s;
+        let mut i = self.i;
+        if i < s.len() && (s[i] == b'+' || s[i] == b'-') {
+            i += 1;
+        }
+        let digits = |i: &mut usize| {
+            while *i < s.len() && s[*i].is_ascii_digit() {
+                *i += 1;
+            }
+        };
+        digits(&mut i);
+        if i < s.len() && s[i] == b'.' {
+            i += 1;
+            digits(&mut i);
+        }
+        if i < s.len() && (s[i] == b'e' || s[i] == b'E') {
+            let mut j = i + 1;
+            if j < s.len() && (s[j] == b'+' || s[j] == b'-') {
+                j += 1;
+            }
+            if j < s.len() && s[j].is_ascii_digit() {
+                i = j;
+                digits(&mut i);
+            }
+        }
+        self.i = i;
+        let text = std::str::from_utf8(&s[start..i]).expect("number bytes are ASCII");
+        text.parse().map_err(|_| {
+            let rest = String::from_utf8_lossy(&s[start..]);
+            import_error(format!("expected a number at `{}`", rest))
+        })
+    }
+
+    // Arc flags may be written without separators, e.g. `a1 1 0 011 1`.
+    fn flag(&mut self) -> Result<bool, ImportError> {
+        self.skip_separators();
+        match self.s.get(self.i) {
+            Some(b'0') => {
+                self.i += 1;
+                Ok(false)
+            }
+            Some(b'1') => {
+                self.i += 1;
+                Ok(true)
+            }
+            _ => Err(import_error("expected an arc flag")),
+        }
+    }
+}
+
+fn import_error<S>(message: S) -> ImportError
+where
+    S: Into<String>,
+{
+    ImportError {
+        message: message.into(),
+    }
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "failed to import SVG: {}", self.message)
+    }
+}
+
+impl std::error::Error for ImportError {}
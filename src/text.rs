@@ -0,0 +1,520 @@
+//! A canonical, human-readable text representation of IDTF.
+//!
+//! The text format is intended for diffing and reviewing show content under version control.
+//! [**to_text**](./fn.to_text.html) produces the text for an IDTF stream and
+//! [**from_text**](./fn.from_text.html) parses it back into byte-identical IDTF.
+//!
+//! Each section begins with a header line followed by one indented line per record:
+//!
+//! ```text
+//! section Coords2dTrueColor name="Frame" company="" number=0 total=1 projector=0
+//!   -100 200 - #ff0000
+//!   100 200 blank|last #000000
+//! end Coords3dIndexedColor name="" company="" number=0 total=0 projector=0
+//! ```
+//!
+//! Records are written as follows, where `status` is `-` or a `|` separated list of `blank`,
+//! `last` and the hex value of any bits undefined by the specification:
+//!
+//! - **Coords3dIndexedColor**: `x y z status index`
+//! - **Coords2dIndexedColor**: `x y status index`
+//! - **ColorPalette**: `#rrggbb`
+//! - **Coords3dTrueColor**: `x y z status #rrggbb`
+//! - **Coords2dTrueColor**: `x y status #rrggbb`
+//!
+//! The number of records is implied by the number of record lines. The end of file header, if
+//! any, is written as an `end` line. Names are quoted with non-printable bytes escaped as `\xNN`.
+//! Non-zero `reserved` and `reserved2` header fields are written as additional `reserved=` and
+//! `reserved2=` fields. Blank lines and lines beginning with `//` are ignored.
+
+use crate::{layout, Error, SectionReader, SectionWriter, SubsectionLayout, SubsectionReaderKind};
+use std::{fmt, fmt::Write as FmtWrite, io::Read};
+use zerocopy::AsBytes;
+
+type I16 = zerocopy::byteorder::I16<layout::Endianness>;
+
+/// An error produced while parsing the text representation.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ParseError {
+    /// The line on which the error occurred, counting from 1.
+    pub line: usize,
+    /// A description of the error.
+    pub message: String,
+}
+
+/// Produce the text representation of all sections read from the given IDTF stream.
+///
+/// Reading stops at the end of file header, or the end of the stream. Any bytes following the end
+/// of file header, or a partial header at the end of the stream, are not represented.
+pub fn to_text<R>(reader: R) -> Result<String, Error>
+where
+    R: Read,
+{
+    let mut reader = SectionReader::new(reader);
+    let mut text = String::new();
+    while let Some(section) = reader.read_next()? {
+        write_header(&mut text, "section", section.header);
+        match section.reader {
+            SubsectionReaderKind::Coords3dIndexedColor(mut r) => {
+                while let Some(p) = r.read_next()? {
+                    let c = &p.coords;
+                    let (x, y, z) = (c.x.get(), c.y.get(), c.z.get());
+                    let status = StatusText(p.status);
+                    writeln!(text, "  {} {} {} {} {}", x, y, z, status, p.color_index).unwrap();
+                }
+            }
+            SubsectionReaderKind::Coords2dIndexedColor(mut r) => {
+                while let Some(p) = r.read_next()? {
+                    let (x, y) = (p.coords.x.get(), p.coords.y.get());
+                    let status = StatusText(p.status);
+                    writeln!(text, "  {} {} {} {}", x, y, status, p.color_index).unwrap();
+                }
+            }
+            SubsectionReaderKind::ColorPalette(mut r) => {
+                while let Some(p) = r.read_next()? {
+                    writeln!(text, "  {}", ColorText(p.color)).unwrap();
+                }
+            }
+            SubsectionReaderKind::Coords3dTrueColor(mut r) => {
+                while let Some(p) = r.read_next()? {
+                    let c = &p.coords;
+                    let (x, y, z) = (c.x.get(), c.y.get(), c.z.get());
+                    let (status, color) = (StatusText(p.status), ColorText(p.color));
+                    writeln!(text, "  {} {} {} {} {}", x, y, z, status, color).unwrap();
+                }
+            }
+            SubsectionReaderKind::Coords2dTrueColor(mut r) => {
+                while let Some(p) = r.read_next()? {
+                    let (x, y) = (p.coords.x.get(), p.coords.y.get());
+                    let (status, color) = (StatusText(p.status), ColorText(p.color));
+                    writeln!(text, "  {} {} {} {}", x, y, status, color).unwrap();
+                }
+            }
+        }
+    }
+    if let Some(header) = reader.end_of_file() {
+        write_header(&mut text, "end", header);
+    }
+    Ok(text)
+}
+
+/// Parse the text representation, producing the equivalent IDTF bytes.
+pub fn from_text(text: &str) -> Result<Vec<u8>, ParseError> {
+    let mut writer = SectionWriter::new(vec![]);
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with("//"))
+        .peekable();
+    while let Some((n, line)) = lines.next() {
+        let (keyword, mut header) =
+            parse_header(line).map_err(|message| ParseError { line: n, message })?;
+
+        // Collect the record lines that follow the header.
+        let mut records = vec![];
+        while let Some(&(n, line)) = lines.peek() {
+            if line.starts_with("section") || line.starts_with("end") {
+                break;
+            }
+            records.push((n, line));
+            lines.next();
+        }
+
+        if keyword == "end" {
+            if let Some(&(n, _)) = records.first() {
+                let message = "the end of file header may not contain records".to_string();
+                return Err(ParseError { line: n, message });
+            }
+            if let Some((n, _)) = lines.next() {
+                let message = "unexpected line following the end of file header".to_string();
+                return Err(ParseError { line: n, message });
+            }
+            let mut bytes = writer.into_inner();
+            bytes.extend_from_slice(header.as_bytes());
+            return Ok(bytes);
+        }
+
+        if records.is_empty() || records.len() > u16::MAX as usize {
+            let message = format!(
+                "sections must contain 1 to 65535 records, found {}",
+                records.len()
+            );
+            return Err(ParseError { line: n, message });
+        }
+        header.num_records.set(records.len() as u16);
+        let result = match header.format {
+            layout::Format::COORDS_3D_INDEXED_COLOR => write_records(
+                &mut writer,
+                &header,
+                &records,
+                parse_coords_3d_indexed_color,
+            ),
+            layout::Format::COORDS_2D_INDEXED_COLOR => write_records(
+                &mut writer,
+                &header,
+                &records,
+                parse_coords_2d_indexed_color,
+            ),
+            layout::Format::COLOR_PALETTE => {
+                write_records(&mut writer, &header, &records, parse_color_palette)
+            }
+            layout::Format::COORDS_3D_TRUE_COLOR => {
+                write_records(&mut writer, &header, &records, parse_coords_3d_true_color)
+            }
+            layout::Format::COORDS_2D_TRUE_COLOR => {
+                write_records(&mut writer, &header, &records, parse_coords_2d_true_color)
+            }
+            format => Err(ParseError {
+                line: n,
+                message: format!("sections may not use unknown format code {}", format.0),
+            }),
+        };
+        result?;
+    }
+    Ok(writer.into_inner())
+}
+
+fn write_header(text: &mut String, keyword: &str, h: &layout::Header) {
+    write!(
+        text,
+        "{} {} name={} company={} number={} total={} projector={}",
+        keyword,
+        FormatText(h.format),
+        NameText(&h.data_name),
+        NameText(&h.company_name),
+        h.data_number.get(),
+        h.color_or_total_frames.get(),
+        h.projector_number,
+    )
+    .unwrap();
+    if h.reserved != [0; 3] {
+        let [a, b, c] = h.reserved;
+        write!(text, " reserved={},{},{}", a, b, c).unwrap();
+    }
+    if h.reserved2 != 0 {
+        write!(text, " reserved2={}", h.reserved2).unwrap();
+    }
+    text.push('\n');
+}
+
+// Parse the keyword and fields of a header line. `num_records` is left zeroed.
+fn parse_header(line: &str) -> Result<(&str, layout::Header), String> {
+    let tokens = tokenize(line)?;
+    let keyword = match tokens.first() {
+        Some(&"section") => "section",
+        Some(&"end") => "end",
+        _ => {
+            return Err(format!(
+                "expected a `section` or `end` header, found `{}`",
+                line
+            ))
+        }
+    };
+    let format = match tokens.get(1) {
+        None => return Err("expected a format".to_string()),
+        Some(s) => parse_format(s)?,
+    };
+    let mut header = layout::Header::new(format, 0);
+    for token in &tokens[2..] {
+        let (key, value) = match token.find('=') {
+            None => return Err(format!("expected a `key=value` field, found `{}`", token)),
+            Some(i) => (&token[..i], &token[i + 1..]),
+        };
+        match key {
+            "name" => header.data_name = parse_name(value)?,
+            "company" => header.company_name = parse_name(value)?,
+            "number" => header.data_number.set(parse_num(value)?),
+            "total" => header.color_or_total_frames.set(parse_num(value)?),
+            "projector" => header.projector_number = parse_num(value)?,
+            "reserved" => {
+                let bytes: Vec<u8> = value.split(',').map(parse_num).collect::<Result<_, _>>()?;
+                if bytes.len() != 3 {
+                    return Err(format!("expected 3 reserved bytes, found `{}`", value));
+                }
+                header.reserved.copy_from_slice(&bytes);
+            }
+            "reserved2" => header.reserved2 = parse_num(value)?,
+            _ => return Err(format!("unknown header field `{}`", key)),
+        }
+    }
+    Ok((keyword, header))
+}
+
+fn write_records<T>(
+    writer: &mut SectionWriter<Vec<u8>>,
+    header: &layout::Header,
+    records: &[(usize, &str)],
+    parse: fn(&[&str]) -> Result<T, String>,
+) -> Result<(), ParseError>
+where
+    T: SubsectionLayout,
+{
+    let records = records
+        .iter()
+        .map(|&(line, s)| {
+            let fields: Vec<&str> = s.split_whitespace().collect();
+            parse(&fields).map_err(|message| ParseError { line, message })
+        })
+        .collect::<Result<Vec<T>, _>>()?;
+    writer
+        .write_section(header, &records)
+        .expect("writing to a `Vec` cannot fail and the header matches the records");
+    Ok(())
+}
+
+fn parse_coords_3d_indexed_color(f: &[&str]) -> Result<layout::Coords3dIndexedColor, String> {
+    expect_fields(f, 5)?;
+    Ok(layout::Coords3dIndexedColor {
+        coords: parse_coords_3d(&f[..3])?,
+        status: parse_status(f[3])?,
+        color_index: parse_num(f[4])?,
+    })
+}
+
+fn parse_coords_2d_indexed_color(f: &[&str]) -> Result<layout::Coords2dIndexedColor, String> {
+    expect_fields(f, 4)?;
+    Ok(layout::Coords2dIndexedColor {
+        coords: parse_coords_2d(&f[..2])?,
+        status: parse_status(f[2])?,
+        color_index: parse_num(f[3])?,
+    })
+}
+
+fn parse_color_palette(f: &[&str]) -> Result<layout::ColorPalette, String> {
+    expect_fields(f, 1)?;
+    Ok(layout::ColorPalette {
+        color: parse_color(f[0])?,
+    })
+}
+
+fn parse_coords_3d_true_color(f: &[&str]) -> Result<layout::Coords3dTrueColor, String> {
+    expect_fields(f, 5)?;
+    Ok(layout::Coords3dTrueColor {
+        coords: parse_coords_3d(&f[..3])?,
+        status: parse_status(f[3])?,
+        color: parse_color(f[4])?,
+    })
+}
+
+fn parse_coords_2d_true_color(f: &[&str]) -> Result<layout::Coords2dTrueColor, String> {
+    expect_fields(f, 4)?;
+    Ok(layout::Coords2dTrueColor {
+        coords: parse_coords_2d(&f[..2])?,
+        status: parse_status(f[2])?,
+        color: parse_color(f[3])?,
+    })
+}
+
+fn expect_fields(fields: &[&str], n: usize) -> Result<(), String> {
+    if fields.len() != n {
+        let msg = format!("expected {} record fields, found {}", n, fields.len());
+        return Err(msg);
+    }
+    Ok(())
+}
+
+fn parse_coords_3d(f: &[&str]) -> Result<layout::Coords3d, String> {
+    Ok(layout::Coords3d {
+        x: I16::new(parse_num(f[0])?),
+        y: I16::new(parse_num(f[1])?),
+        z: I16::new(parse_num(f[2])?),
+    })
+}
+
+fn parse_coords_2d(f: &[&str]) -> Result<layout::Coords2d, String> {
+    Ok(layout::Coords2d {
+        x: I16::new(parse_num(f[0])?),
+        y: I16::new(parse_num(f[1])?),
+    })
+}
+
+fn parse_num<T>(s: &str) -> Result<T, String>
+where
+    T: std::str::FromStr,
+{
+    s.parse().map_err(|_| format!("invalid number `{}`", s))
+}
+
+fn parse_format(s: &str) -> Result<layout::Format, String> {
+    let format = match s {
+        "Coords3dIndexedColor" => layout::Format::COORDS_3D_INDEXED_COLOR,
+        "Coords2dIndexedColor" => layout::Format::COORDS_2D_INDEXED_COLOR,
+        "ColorPalette" => layout::Format::COLOR_PALETTE,
+        "Coords3dTrueColor" => layout::Format::COORDS_3D_TRUE_COLOR,
+        "Coords2dTrueColor" => layout::Format::COORDS_2D_TRUE_COLOR,
+        s => layout::Format(parse_num(s).map_err(|_| format!("unknown format `{}`", s))?),
+    };
+    Ok(format)
+}
+
+fn parse_status(s: &str) -> Result<layout::Status, String> {
+    let mut bits = 0u8;
+    if s != "-" {
+        for flag in s.split('|') {
+            bits |= match flag {
+                "blank" => layout::Status::BLANKING.bits(),
+                "last" => layout::Status::LAST_POINT.bits(),
+                hex if hex.starts_with("0x") => u8::from_str_radix(&hex[2..], 16)
+                    .map_err(|_| format!("invalid status bits `{}`", hex))?,
+                flag => return Err(format!("unknown status flag `{}`", flag)),
+            };
+        }
+    }
+    // Map the byte directly in order to retain undefined bits.
+    let bytes = [bits];
+    let status = zerocopy::LayoutVerified::<_, layout::Status>::new(&bytes[..])
+        .map(zerocopy::LayoutVerified::into_ref)
+        .expect("a single byte must match the layout of `Status`");
+    Ok(*status)
+}
+
+fn parse_color(s: &str) -> Result<layout::Color, String> {
+    let err = || format!("expected a `#rrggbb` color, found `{}`", s);
+    if s.len() != 7 || !s.starts_with('#') {
+        return Err(err());
+    }
+    let channel = |i: usize| u8::from_str_radix(&s[i..i + 2], 16).map_err(|_| err());
+    Ok(layout::Color {
+        red: channel(1)?,
+        green: channel(3)?,
+        blue: channel(5)?,
+    })
+}
+
+fn parse_name(s: &str) -> Result<layout::Name, String> {
+    let inner = s
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .ok_or_else(|| format!("expected a quoted name, found `{}`", s))?;
+    let mut bytes = vec![];
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('\\') => bytes.push(b'\\'),
+                Some('"') => bytes.push(b'"'),
+                Some('x') => {
+                    let hex: String = chars.by_ref().take(2).collect();
+                    let byte = u8::from_str_radix(&hex, 16)
+                        .map_err(|_| format!("invalid escape `\\x{}`", hex))?;
+                    bytes.push(byte);
+                }
+                _ => return Err(format!("invalid escape in name {}", s)),
+            },
+            c if c.is_ascii() => bytes.push(c as u8),
+            c => return Err(format!("non-ASCII character `{}` must be escaped", c)),
+        }
+    }
+    let mut name = [0u8; 8];
+    if bytes.len() > name.len() {
+        return Err(format!("name {} exceeds 8 bytes", s));
+    }
+    name[..bytes.len()].copy_from_slice(&bytes);
+    Ok(layout::Name(name))
+}
+
+// Split the line at whitespace outside of quoted names.
+fn tokenize(line: &str) -> Result<Vec<&str>, String> {
+    let mut tokens = vec![];
+    let mut start = None;
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if let Some(s) = start.take() {
+                    tokens.push(&line[s..i]);
+                }
+                continue;
+            }
+            _ => (),
+        }
+        start.get_or_insert(i);
+    }
+    if quoted {
+        return Err(format!("unterminated quote in `{}`", line));
+    }
+    if let Some(s) = start {
+        tokens.push(&line[s..]);
+    }
+    Ok(tokens)
+}
+
+struct FormatText(layout::Format);
+struct NameText<'a>(&'a layout::Name);
+struct StatusText(layout::Status);
+struct ColorText(layout::Color);
+
+impl fmt::Display for FormatText {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            layout::Format::COORDS_3D_INDEXED_COLOR => write!(f, "Coords3dIndexedColor"),
+            layout::Format::COORDS_2D_INDEXED_COLOR => write!(f, "Coords2dIndexedColor"),
+            layout::Format::COLOR_PALETTE => write!(f, "ColorPalette"),
+            layout::Format::COORDS_3D_TRUE_COLOR => write!(f, "Coords3dTrueColor"),
+            layout::Format::COORDS_2D_TRUE_COLOR => write!(f, "Coords2dTrueColor"),
+            format => write!(f, "{}", format.0),
+        }
+    }
+}
+
+impl<'a> fmt::Display for NameText<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Trailing zeros are implied.
+        let bytes = &(self.0).0;
+        let len = bytes
+            .iter()
+            .rposition(|&b| b != 0)
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        f.write_str("\"")?;
+        for &b in &bytes[..len] {
+            match b {
+                b'"' => f.write_str("\\\"")?,
+                b'\\' => f.write_str("\\\\")?,
+                b' '..=b'~' => write!(f, "{}", b as char)?,
+                b => write!(f, "\\x{:02x}", b)?,
+            }
+        }
+        f.write_str("\"")
+    }
+}
+
+impl fmt::Display for StatusText {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let undefined = self.0.bits() & !layout::Status::all().bits();
+        let mut flags = vec![];
+        if self.0.is_blanking() {
+            flags.push("blank".to_string());
+        }
+        if self.0.is_last_point() {
+            flags.push("last".to_string());
+        }
+        if undefined != 0 {
+            flags.push(format!("{:#04x}", undefined));
+        }
+        if flags.is_empty() {
+            return f.write_str("-");
+        }
+        f.write_str(&flags.join("|"))
+    }
+}
+
+impl fmt::Display for ColorText {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let c = self.0;
+        write!(f, "#{:02x}{:02x}{:02x}", c.red, c.green, c.blue)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
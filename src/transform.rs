@@ -0,0 +1,441 @@
+//! Items related to geometric transformation of frame points.
+//!
+//! Transforms are described by the [**Affine2**](./struct.Affine2.html),
+//! [**Affine3**](./struct.Affine3.html) and [**Homography**](./struct.Homography.html) types and
+//! may be applied to the points of a frame via [**apply_2d**](./fn.apply_2d.html) and
+//! [**apply_3d**](./fn.apply_3d.html). As the coordinate range of IDTF is limited to that of
+//! `i16`, transformed coordinates beyond this range are either clamped or reported depending on
+//! the given [**Overflow**](./enum.Overflow.html) behaviour. Coordinates never wrap.
+
+use crate::layout;
+use std::fmt;
+
+type I16 = zerocopy::byteorder::I16<layout::Endianness>;
+
+/// Types that may transform 2D coordinates.
+pub trait Transform2 {
+    /// Transform the given coordinates.
+    fn transform(&self, point: [f32; 2]) -> [f32; 2];
+}
+
+/// Types that may transform 3D coordinates.
+pub trait Transform3 {
+    /// Transform the given coordinates.
+    fn transform(&self, point: [f32; 3]) -> [f32; 3];
+}
+
+/// Point layouts with 2D coordinates.
+pub trait Coords2dMut {
+    /// Mutable access to the coordinates of the point.
+    fn coords_mut(&mut self) -> &mut layout::Coords2d;
+}
+
+/// Point layouts with 3D coordinates.
+pub trait Coords3dMut {
+    /// Mutable access to the coordinates of the point.
+    fn coords_mut(&mut self) -> &mut layout::Coords3d;
+}
+
+/// A 2D affine transform, e.g. any combination of scaling, rotation, translation and mirroring.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Affine2 {
+    /// The first two rows of a 3x3 row-major matrix applied to column vectors `[x, y, 1]`.
+    pub matrix: [[f32; 3]; 2],
+}
+
+/// A 3D affine transform, e.g. any combination of scaling, rotation, translation and mirroring.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Affine3 {
+    /// The first three rows of a 4x4 row-major matrix applied to column vectors `[x, y, z, 1]`.
+    pub matrix: [[f32; 4]; 3],
+}
+
+/// A 2D projective transform, suitable for keystone and perspective correction.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Homography {
+    /// A 3x3 row-major matrix applied to column vectors `[x, y, 1]`.
+    pub matrix: [[f32; 3]; 3],
+}
+
+/// How to handle transformed coordinates that lie beyond the range of `i16`.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Overflow {
+    /// Clamp each coordinate to the range of `i16`.
+    Clamp,
+    /// Return an error, leaving the points unchanged.
+    Error,
+}
+
+/// A transformed point lay beyond the range of `i16`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct OutOfRange {
+    /// The index of the first offending point within the frame.
+    pub index: usize,
+    /// The transformed coordinates of the point. The z coordinate is 0 for 2D points.
+    pub coords: [f32; 3],
+}
+
+impl Affine2 {
+    /// The transform that leaves coordinates unchanged.
+    pub const IDENTITY: Self = Affine2 {
+        matrix: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+    };
+
+    /// Translate by the given offset.
+    pub fn translation(x: f32, y: f32) -> Self {
+        Affine2 {
+            matrix: [[1.0, 0.0, x], [0.0, 1.0, y]],
+        }
+    }
+
+    /// Scale about the origin by the given factors.
+    ///
+    /// Negative factors mirror the coordinates.
+    pub fn scale(x: f32, y: f32) -> Self {
+        Affine2 {
+            matrix: [[x, 0.0, 0.0], [0.0, y, 0.0]],
+        }
+    }
+
+    /// Rotate counter-clockwise about the origin by the given angle in radians.
+    pub fn rotation(radians: f32) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        Affine2 {
+            matrix: [[cos, -sin, 0.0], [sin, cos, 0.0]],
+        }
+    }
+
+    /// Mirror the x coordinates about the y axis.
+    pub fn mirror_x() -> Self {
+        Self::scale(-1.0, 1.0)
+    }
+
+    /// Mirror the y coordinates about the x axis.
+    pub fn mirror_y() -> Self {
+        Self::scale(1.0, -1.0)
+    }
+
+    /// The transform that applies `self` followed by `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        let [a, b] = next.matrix;
+        let [c, d] = self.matrix;
+        let row = |r: [f32; 3]| {
+            [
+                r[0] * c[0] + r[1] * d[0],
+                r[0] * c[1] + r[1] * d[1],
+                r[0] * c[2] + r[1] * d[2] + r[2],
+            ]
+        };
+        Affine2 {
+            matrix: [row(a), row(b)],
+        }
+    }
+}
+
+impl Affine3 {
+    /// The transform that leaves coordinates unchanged.
+    pub const IDENTITY: Self = Affine3 {
+        matrix: [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+        ],
+    };
+
+    /// Translate by the given offset.
+    pub fn translation(x: f32, y: f32, z: f32) -> Self {
+        Affine3 {
+            matrix: [[1.0, 0.0, 0.0, x], [0.0, 1.0, 0.0, y], [0.0, 0.0, 1.0, z]],
+        }
+    }
+
+    /// Scale about the origin by the given factors.
+    ///
+    /// Negative factors mirror the coordinates.
+    pub fn scale(x: f32, y: f32, z: f32) -> Self {
+        Affine3 {
+            matrix: [[x, 0.0, 0.0, 0.0], [0.0, y, 0.0, 0.0], [0.0, 0.0, z, 0.0]],
+        }
+    }
+
+    /// Rotate counter-clockwise about the x axis by the given angle in radians.
+    pub fn rotation_x(radians: f32) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        Affine3 {
+            matrix: [
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, cos, -sin, 0.0],
+                [0.0, sin, cos, 0.0],
+            ],
+        }
+    }
+
+    /// Rotate counter-clockwise about the y axis by the given angle in radians.
+    pub fn rotation_y(radians: f32) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        Affine3 {
+            matrix: [
+                [cos, 0.0, sin, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [-sin, 0.0, cos, 0.0],
+            ],
+        }
+    }
+
+    /// Rotate counter-clockwise about the z axis by the given angle in radians.
+    pub fn rotation_z(radians: f32) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        Affine3 {
+            matrix: [
+                [cos, -sin, 0.0, 0.0],
+                [sin, cos, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+            ],
+        }
+    }
+
+    /// The transform that applies `self` followed by `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        let m = self.matrix;
+        let row = |r: [f32; 4]| {
+            let mut out = [0.0; 4];
+            for (j, o) in out.iter_mut().enumerate() {
+                *o = r[0] * m[0][j] + r[1] * m[1][j] + r[2] * m[2][j];
+            }
+            out[3] += r[3];
+            out
+        };
+        let [a, b, c] = next.matrix;
+        Affine3 {
+            matrix: [row(a), row(b), row(c)],
+        }
+    }
+}
+
+impl Homography {
+    /// The transform that leaves coordinates unchanged.
+    pub const IDENTITY: Self = Homography {
+        matrix: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+    };
+
+    /// Keystone correction mapping the corners of the full coordinate range onto the given
+    /// corners.
+    ///
+    /// The corners are given in the order bottom-left, bottom-right, top-right, top-left, i.e.
+    /// the targets for `(-32767, -32767)`, `(32767, -32767)`, `(32767, 32767)` and
+    /// `(-32767, 32767)` respectively.
+    ///
+    /// Returns `None` if three or more of the corners are collinear.
+    pub fn keystone(corners: [[f32; 2]; 4]) -> Option<Self> {
+        // Map the unit square onto the quad, as per Heckbert's "Fundamentals of Texture Mapping
+        // and Image Warping".
+        let [[x0, y0], [x1, y1], [x2, y2], [x3, y3]] = corners;
+        let (dx3, dy3) = (x0 - x1 + x2 - x3, y0 - y1 + y2 - y3);
+        let square_to_quad = if dx3 == 0.0 && dy3 == 0.0 {
+            [
+                [x1 - x0, x2 - x1, x0],
+                [y1 - y0, y2 - y1, y0],
+                [0.0, 0.0, 1.0],
+            ]
+        } else {
+            let (dx1, dx2) = (x1 - x2, x3 - x2);
+            let (dy1, dy2) = (y1 - y2, y3 - y2);
+            let det = dx1 * dy2 - dx2 * dy1;
+            if det == 0.0 {
+                return None;
+            }
+            let g = (dx3 * dy2 - dx2 * dy3) / det;
+            let h = (dx1 * dy3 - dx3 * dy1) / det;
+            [
+                [x1 - x0 + g * x1, x3 - x0 + h * x3, x0],
+                [y1 - y0 + g * y1, y3 - y0 + h * y3, y0],
+                [g, h, 1.0],
+            ]
+        };
+        let homography = Homography {
+            matrix: square_to_quad,
+        };
+        if homography.determinant() == 0.0 {
+            return None;
+        }
+
+        // Map the full coordinate range onto the unit square.
+        let max = i16::MAX as f32;
+        let normalize = Affine2::translation(max, max).then(&Affine2::scale(0.5 / max, 0.5 / max));
+        Some(Homography::from(normalize).then(&homography))
+    }
+
+    /// The transform that applies `self` followed by `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        let (a, b) = (next.matrix, self.matrix);
+        let mut matrix = [[0.0; 3]; 3];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, v) in row.iter_mut().enumerate() {
+                *v = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+            }
+        }
+        Homography { matrix }
+    }
+
+    fn determinant(&self) -> f32 {
+        let m = self.matrix;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+}
+
+impl Default for Affine2 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Default for Affine3 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Default for Homography {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl From<Affine2> for Homography {
+    fn from(affine: Affine2) -> Self {
+        let [a, b] = affine.matrix;
+        Homography {
+            matrix: [a, b, [0.0, 0.0, 1.0]],
+        }
+    }
+}
+
+impl Transform2 for Affine2 {
+    fn transform(&self, [x, y]: [f32; 2]) -> [f32; 2] {
+        let [a, b] = self.matrix;
+        [a[0] * x + a[1] * y + a[2], b[0] * x + b[1] * y + b[2]]
+    }
+}
+
+impl Transform3 for Affine3 {
+    fn transform(&self, [x, y, z]: [f32; 3]) -> [f32; 3] {
+        let row = |r: [f32; 4]| r[0] * x + r[1] * y + r[2] * z + r[3];
+        let [a, b, c] = self.matrix;
+        [row(a), row(b), row(c)]
+    }
+}
+
+impl Transform2 for Homography {
+    /// Points mapped to infinity produce infinite or NaN coordinates.
+    fn transform(&self, [x, y]: [f32; 2]) -> [f32; 2] {
+        let row = |r: [f32; 3]| r[0] * x + r[1] * y + r[2];
+        let [a, b, c] = self.matrix;
+        let w = row(c);
+        [row(a) / w, row(b) / w]
+    }
+}
+
+impl Coords2dMut for layout::Coords2dIndexedColor {
+    fn coords_mut(&mut self) -> &mut layout::Coords2d {
+        &mut self.coords
+    }
+}
+
+impl Coords2dMut for layout::Coords2dTrueColor {
+    fn coords_mut(&mut self) -> &mut layout::Coords2d {
+        &mut self.coords
+    }
+}
+
+impl Coords3dMut for layout::Coords3dIndexedColor {
+    fn coords_mut(&mut self) -> &mut layout::Coords3d {
+        &mut self.coords
+    }
+}
+
+impl Coords3dMut for layout::Coords3dTrueColor {
+    fn coords_mut(&mut self) -> &mut layout::Coords3d {
+        &mut self.coords
+    }
+}
+
+/// Apply the 2D transform to each point of the frame.
+///
+/// With **Overflow::Error**, the points are only modified if all transformed coordinates lie
+/// within the range of `i16`. NaN coordinates are considered out of range, or clamp to 0.
+pub fn apply_2d<P, T>(points: &mut [P], transform: &T, overflow: Overflow) -> Result<(), OutOfRange>
+where
+    P: Coords2dMut,
+    T: Transform2 + ?Sized,
+{
+    let transformed = |p: &mut P| {
+        let c = p.coords_mut();
+        transform.transform([c.x.get() as f32, c.y.get() as f32])
+    };
+    if overflow == Overflow::Error {
+        for (index, p) in points.iter_mut().enumerate() {
+            let [x, y] = transformed(p);
+            if !in_range(x) || !in_range(y) {
+                let coords = [x, y, 0.0];
+                return Err(OutOfRange { index, coords });
+            }
+        }
+    }
+    for p in points.iter_mut() {
+        let [x, y] = transformed(p);
+        let c = p.coords_mut();
+        c.x = I16::new(crate::saturate(x));
+        c.y = I16::new(crate::saturate(y));
+    }
+    Ok(())
+}
+
+/// Apply the 3D transform to each point of the frame.
+///
+/// See [**apply_2d**](./fn.apply_2d.html).
+pub fn apply_3d<P, T>(points: &mut [P], transform: &T, overflow: Overflow) -> Result<(), OutOfRange>
+where
+    P: Coords3dMut,
+    T: Transform3 + ?Sized,
+{
+    let transformed = |p: &mut P| {
+        let c = p.coords_mut();
+        transform.transform([c.x.get() as f32, c.y.get() as f32, c.z.get() as f32])
+    };
+    if overflow == Overflow::Error {
+        for (index, p) in points.iter_mut().enumerate() {
+            let coords = transformed(p);
+            if !coords.iter().all(|&v| in_range(v)) {
+                return Err(OutOfRange { index, coords });
+            }
+        }
+    }
+    for p in points.iter_mut() {
+        let [x, y, z] = transformed(p);
+        let c = p.coords_mut();
+        c.x = I16::new(crate::saturate(x));
+        c.y = I16::new(crate::saturate(y));
+        c.z = I16::new(crate::saturate(z));
+    }
+    Ok(())
+}
+
+// Whether or not the coordinate rounds to a value within the range of `i16`.
+fn in_range(v: f32) -> bool {
+    let v = v.round();
+    v >= i16::MIN as f32 && v <= i16::MAX as f32
+}
+
+impl fmt::Display for OutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "transformed point {} at {:?} lies beyond the range of `i16`",
+            self.index, self.coords,
+        )
+    }
+}
+
+impl std::error::Error for OutOfRange {}
@@ -0,0 +1,280 @@
+//! Items related to validating the conformance of IDTF content with the specification.
+//!
+//! The [**validate**](./fn.validate.html) function walks every section of an IDTF stream and
+//! reports every deviation from Revision 011 of the specification that it encounters, rather than
+//! stopping at the first.
+
+use crate::{
+    layout, Error, LayoutBuffer, Position, SectionReader, SubsectionReader, SubsectionReaderKind,
+};
+use std::{fmt, io::Read, mem, path::Path};
+
+/// A single deviation from the specification.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Violation {
+    /// The location of the offending header or record.
+    pub position: Position,
+    /// The kind of violation.
+    pub kind: ViolationKind,
+}
+
+/// The kinds of deviation from the specification that are detected.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum ViolationKind {
+    /// One of the reserved header fields was not zeroed.
+    NonZeroReserved,
+    /// A color palette contains fewer than 2 or more than 256 colors.
+    InvalidPaletteSize { len: u16 },
+    /// The `color_or_total_frames` field of a color palette header was not zeroed.
+    NonZeroPaletteTotal { total: u16 },
+    /// The `data_number` field exceeds the maximum of 65534.
+    DataNumberOutOfRange { number: u16 },
+    /// The `color_or_total_frames` field of a frame header was zero.
+    ZeroTotalFrames,
+    /// A frame specifies a different total number of frames to the preceding frames of its
+    /// sequence.
+    TotalFramesMismatch { expected: u16, found: u16 },
+    /// A name contains bytes that are not ASCII.
+    NonAsciiName { field: NameField },
+    /// The final point of a frame does not have the `LAST_POINT` status bit set.
+    MissingLastPoint,
+    /// A point other than the final point of a frame has the `LAST_POINT` status bit set.
+    EarlyLastPoint { index: u16 },
+    /// A point's status byte has bits set that are not defined by the specification.
+    UndefinedStatusBits { index: u16, bits: u8 },
+    /// The stream ended without an end of file header.
+    MissingEndOfFile,
+}
+
+/// The header field containing a name.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum NameField {
+    DataName,
+    CompanyName,
+}
+
+/// The result of validating an IDTF stream.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Report {
+    /// The number of sections that were validated, excluding the end of file header.
+    pub sections: usize,
+    /// All violations in the order in which they were encountered.
+    pub violations: Vec<Violation>,
+}
+
+impl Report {
+    /// Whether or not the stream conforms to the specification.
+    pub fn is_conforming(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+// The sequence of frames currently being validated for a projector.
+struct Sequence {
+    projector: u8,
+    total: u16,
+}
+
+/// Validate all sections read from the given reader.
+///
+/// Returns an error only if the stream cannot be read at all beyond a certain point, e.g. due to
+/// an invalid header or truncated section. All other deviations are collected into the returned
+/// **Report**.
+pub fn validate<R>(reader: R) -> Result<Report, Error>
+where
+    R: Read,
+{
+    validate_sections(SectionReader::new(reader))
+}
+
+/// Validate the IDTF file at the given path.
+///
+/// See [**validate**](./fn.validate.html).
+pub fn validate_path<P>(path: P) -> Result<Report, Error>
+where
+    P: AsRef<Path>,
+{
+    validate_sections(crate::open(path)?)
+}
+
+// Validate all sections of a non-strict reader that stops at the end of file header.
+fn validate_sections<R>(mut reader: SectionReader<R>) -> Result<Report, Error>
+where
+    R: Read,
+{
+    let mut report = Report::default();
+    let mut sequences: Vec<Sequence> = vec![];
+    let violations = &mut report.violations;
+    loop {
+        let position = reader.position();
+        let section = match reader.read_next()? {
+            None => break,
+            Some(section) => section,
+        };
+        report.sections += 1;
+        let header = section.header;
+        let mut violation = |kind| violations.push(Violation { position, kind });
+
+        if header.reserved != [0; 3] || header.reserved2 != 0 {
+            violation(ViolationKind::NonZeroReserved);
+        }
+        if !is_ascii(&header.data_name) {
+            let field = NameField::DataName;
+            violation(ViolationKind::NonAsciiName { field });
+        }
+        if !is_ascii(&header.company_name) {
+            let field = NameField::CompanyName;
+            violation(ViolationKind::NonAsciiName { field });
+        }
+        let number = header.data_number.get();
+        if number > 65534 {
+            violation(ViolationKind::DataNumberOutOfRange { number });
+        }
+
+        let total = header.color_or_total_frames.get();
+        let projector = header.projector_number;
+        if header.format == layout::Format::COLOR_PALETTE {
+            let len = header.num_records.get();
+            if !(2..=256).contains(&len) {
+                violation(ViolationKind::InvalidPaletteSize { len });
+            }
+            if total != 0 {
+                violation(ViolationKind::NonZeroPaletteTotal { total });
+            }
+        } else if total == 0 {
+            violation(ViolationKind::ZeroTotalFrames);
+        } else {
+            // A frame numbered 0 begins a new sequence for its projector.
+            match sequences.iter_mut().find(|s| s.projector == projector) {
+                Some(sequence) if number != 0 => {
+                    if sequence.total != total {
+                        let expected = sequence.total;
+                        let found = total;
+                        violation(ViolationKind::TotalFramesMismatch { expected, found });
+                    }
+                }
+                Some(sequence) => sequence.total = total,
+                None => sequences.push(Sequence { projector, total }),
+            }
+        }
+
+        let violations = &mut *violations;
+        match section.reader {
+            SubsectionReaderKind::Coords3dIndexedColor(r) => {
+                validate_points(r, position, violations)?
+            }
+            SubsectionReaderKind::Coords2dIndexedColor(r) => {
+                validate_points(r, position, violations)?
+            }
+            SubsectionReaderKind::ColorPalette(_) => (),
+            SubsectionReaderKind::Coords3dTrueColor(r) => validate_points(r, position, violations)?,
+            SubsectionReaderKind::Coords2dTrueColor(r) => validate_points(r, position, violations)?,
+        }
+    }
+
+    if reader.end_of_file().is_none() {
+        let position = reader.position();
+        let kind = ViolationKind::MissingEndOfFile;
+        report.violations.push(Violation { position, kind });
+    }
+
+    Ok(report)
+}
+
+// Validate the status of each point within a frame.
+fn validate_points<R, T>(
+    mut reader: SubsectionReader<R, T>,
+    position: Position,
+    violations: &mut Vec<Violation>,
+) -> Result<(), Error>
+where
+    R: Read,
+    T: LayoutBuffer + layout::PointLayout,
+{
+    let header_size = mem::size_of::<layout::Header>() as u64;
+    let record_size = mem::size_of::<T>() as u64;
+    let last = reader.len().saturating_sub(1);
+    let mut index = 0;
+    while let Some(point) = reader.read_next()? {
+        let status = point.status();
+        let position = Position {
+            section: position.section,
+            offset: position.offset + header_size + index as u64 * record_size,
+        };
+        let mut violation = |kind| violations.push(Violation { position, kind });
+        let bits = status.bits() & !layout::Status::all().bits();
+        if bits != 0 {
+            violation(ViolationKind::UndefinedStatusBits { index, bits });
+        }
+        if index == last && !status.is_last_point() {
+            violation(ViolationKind::MissingLastPoint);
+        } else if index != last && status.is_last_point() {
+            violation(ViolationKind::EarlyLastPoint { index });
+        }
+        index += 1;
+    }
+    Ok(())
+}
+
+// Whether or not the name is ASCII up to the first binary zero.
+fn is_ascii(name: &layout::Name) -> bool {
+    name.0.iter().take_while(|&&b| b != 0).all(u8::is_ascii)
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.position, self.kind)
+    }
+}
+
+impl fmt::Display for ViolationKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ViolationKind::NonZeroReserved => write!(f, "reserved `Header` field not zeroed"),
+            ViolationKind::InvalidPaletteSize { len } => write!(
+                f,
+                "color palette of {} colors is outside the range 2 to 256",
+                len
+            ),
+            ViolationKind::NonZeroPaletteTotal { total } => write!(
+                f,
+                "color palette `color_or_total_frames` is {} rather than 0",
+                total
+            ),
+            ViolationKind::DataNumberOutOfRange { number } => {
+                write!(f, "`data_number` {} exceeds the maximum of 65534", number)
+            }
+            ViolationKind::ZeroTotalFrames => write!(f, "frame total is 0"),
+            ViolationKind::TotalFramesMismatch { expected, found } => write!(
+                f,
+                "frame total {} differs from the sequence total {}",
+                found, expected
+            ),
+            ViolationKind::NonAsciiName { field } => {
+                let field = match field {
+                    NameField::DataName => "data_name",
+                    NameField::CompanyName => "company_name",
+                };
+                write!(f, "`{}` contains non-ASCII bytes", field)
+            }
+            ViolationKind::MissingLastPoint => {
+                write!(f, "final point of frame is missing `LAST_POINT`")
+            }
+            ViolationKind::EarlyLastPoint { index } => {
+                write!(
+                    f,
+                    "point {} is not the final point but sets `LAST_POINT`",
+                    index
+                )
+            }
+            ViolationKind::UndefinedStatusBits { index, bits } => {
+                write!(
+                    f,
+                    "point {} sets undefined status bits {:#010b}",
+                    index, bits
+                )
+            }
+            ViolationKind::MissingEndOfFile => write!(f, "missing end of file header"),
+        }
+    }
+}
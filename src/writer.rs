@@ -0,0 +1,238 @@
+//! Items related to writing IDTF sections to any type implementing `std::io::Write`.
+
+use crate::{layout, Error, Position, SubsectionLayout};
+use std::{io::Write, mem};
+use zerocopy::AsBytes;
+
+/// Writes a sequence of IDTF sections to a stream of bytes.
+///
+/// Writes `Section`s.
+///
+/// **SectionWriter::finish** should be called once all sections have been written in order to
+/// emit the end of file header.
+pub struct SectionWriter<W> {
+    writer: W,
+    position: Position,
+    last_header: Option<layout::Header>,
+}
+
+/// Writes exactly `len` consecutive subsections of type `T`.
+///
+/// The `len` is determined by the `num_records` field of the header that began the section.
+/// **SubsectionWriter::finish** must be called once all subsections have been written in order to
+/// verify that the section is complete.
+pub struct SubsectionWriter<W, T>
+where
+    W: Write,
+    T: SubsectionLayout,
+{
+    writer: W,
+    len: u16,
+    total: u16,
+    position: Position,
+    subsection_layout: std::marker::PhantomData<T>,
+}
+
+pub type Coords3dIndexedColorWriter<W> = SubsectionWriter<W, layout::Coords3dIndexedColor>;
+pub type Coords2dIndexedColorWriter<W> = SubsectionWriter<W, layout::Coords2dIndexedColor>;
+pub type ColorPaletteWriter<W> = SubsectionWriter<W, layout::ColorPalette>;
+pub type Coords3dTrueColorWriter<W> = SubsectionWriter<W, layout::Coords3dTrueColor>;
+pub type Coords2dTrueColorWriter<W> = SubsectionWriter<W, layout::Coords2dTrueColor>;
+
+impl<W> SectionWriter<W>
+where
+    W: Write,
+{
+    /// Write ILDA IDTF sections to the given writer.
+    pub fn new(writer: W) -> Self {
+        let position = Position::default();
+        let last_header = None;
+        SectionWriter {
+            writer,
+            position,
+            last_header,
+        }
+    }
+
+    /// The position at which the next section header will be written.
+    ///
+    /// Assumes that the previously begun section, if any, is written to completion.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// Begin writing a new section with the given **Header**.
+    ///
+    /// The header is written immediately. The returned **SubsectionWriter** expects exactly
+    /// `header.num_records` subsections to be written before it is finished.
+    ///
+    /// Returns an error if the header's `format` does not describe subsections of type `T` or if
+    /// its `num_records` is `0`, as only **finish** may write the end of file header.
+    pub fn begin_section<T>(
+        &mut self,
+        header: &layout::Header,
+    ) -> Result<SubsectionWriter<&mut W, T>, Error>
+    where
+        T: SubsectionLayout,
+    {
+        let position = self.position;
+        verify_header::<T>(header, position)?;
+        self.writer.write_all(header.as_bytes())?;
+        let len = header.num_records.get();
+        self.advance::<T>(header);
+        Ok(SubsectionWriter::new(&mut self.writer, len, position))
+    }
+
+    /// Write a whole section consisting of the given **Header** and subsections.
+    ///
+    /// Returns an error without writing anything if the number of subsections does not match the
+    /// header's `num_records` field, or if there are no subsections, as only **finish** may write
+    /// the end of file header.
+    pub fn write_section<T>(
+        &mut self,
+        header: &layout::Header,
+        subsections: &[T],
+    ) -> Result<(), Error>
+    where
+        T: SubsectionLayout,
+    {
+        let position = self.position;
+        verify_header::<T>(header, position)?;
+        let expected = header.num_records.get();
+        if subsections.len() > expected as usize {
+            return Err(Error::ExcessRecords { position, expected });
+        } else if subsections.len() < expected as usize {
+            let got = subsections.len() as u16;
+            return Err(Error::TruncatedSection {
+                position,
+                expected,
+                got,
+            });
+        }
+        self.writer.write_all(header.as_bytes())?;
+        self.writer.write_all(subsections.as_bytes())?;
+        self.advance::<T>(header);
+        Ok(())
+    }
+
+    /// Flush the inner writer.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Write the end of file header, flush and produce the inner writer.
+    ///
+    /// The end of file header is a copy of the most recently written header with its
+    /// `num_records` and `data_number` fields zeroed. If no sections have been written, a zeroed
+    /// header is used.
+    pub fn finish(mut self) -> Result<W, Error> {
+        let mut header = self
+            .last_header
+            .unwrap_or_else(|| layout::Header::new(layout::Format(0), 0));
+        header.num_records.set(0);
+        header.data_number.set(0);
+        self.writer.write_all(header.as_bytes())?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    /// Consume the **SectionWriter** and produce the inner writer.
+    ///
+    /// Unlike **finish**, no end of file header is written.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    // Advance the position past the section with the given header.
+    fn advance<T>(&mut self, header: &layout::Header) {
+        let len = header.num_records.get() as usize;
+        let size = mem::size_of::<layout::Header>() + len * mem::size_of::<T>();
+        self.position.section += 1;
+        self.position.offset += size as u64;
+        self.last_header = Some(*header);
+    }
+}
+
+impl<W, T> SubsectionWriter<W, T>
+where
+    W: Write,
+    T: SubsectionLayout,
+{
+    fn new(writer: W, len: u16, position: Position) -> Self {
+        let subsection_layout = std::marker::PhantomData;
+        let total = len;
+        Self {
+            writer,
+            len,
+            total,
+            position,
+            subsection_layout,
+        }
+    }
+
+    /// The number of remaining subsections expected.
+    pub fn len(&self) -> u16 {
+        self.len
+    }
+
+    /// Whether or not all expected subsections have been written.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Write the next subsection.
+    ///
+    /// Returns an error if all subsections expected by the header have already been written.
+    pub fn write_next(&mut self, subsection: &T) -> Result<(), Error> {
+        if self.len == 0 {
+            return Err(Error::ExcessRecords {
+                position: self.position,
+                expected: self.total,
+            });
+        }
+        // Only count the subsection once it has been written in full.
+        self.writer.write_all(subsection.as_bytes())?;
+        self.len -= 1;
+        Ok(())
+    }
+
+    /// Finish writing the section.
+    ///
+    /// Returns an error if the number of subsections written does not match the header's
+    /// `num_records` field.
+    pub fn finish(self) -> Result<(), Error> {
+        if self.len != 0 {
+            return Err(Error::TruncatedSection {
+                position: self.position,
+                expected: self.total,
+                got: self.total - self.len,
+            });
+        }
+        Ok(())
+    }
+}
+
+// Ensure the header describes a section of subsections of type `T`.
+fn verify_header<T>(header: &layout::Header, position: Position) -> Result<(), Error>
+where
+    T: SubsectionLayout,
+{
+    if header.ilda != layout::Header::ILDA {
+        return Err(Error::BadMagic {
+            position,
+            found: header.ilda,
+        });
+    }
+    if header.format != T::FORMAT {
+        return Err(Error::UnexpectedFormat {
+            position,
+            expected: T::FORMAT,
+            found: header.format,
+        });
+    }
+    if header.num_records.get() == 0 {
+        return Err(Error::EmptySection { position });
+    }
+    Ok(())
+}
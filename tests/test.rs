@@ -1,3 +1,28 @@
+// The position and blanking of each point.
+fn summary(points: &[ilda_idtf::layout::Coords2dTrueColor]) -> Vec<(i16, i16, bool)> {
+    points
+        .iter()
+        .map(|p| (p.coords.x.get(), p.coords.y.get(), p.status.is_blanking()))
+        .collect()
+}
+
+// A 2D true color point record.
+fn point_2d(
+    x: i16,
+    y: i16,
+    color: ilda_idtf::layout::Color,
+    status: ilda_idtf::layout::Status,
+) -> ilda_idtf::layout::Coords2dTrueColor {
+    ilda_idtf::layout::Coords2dTrueColor {
+        coords: ilda_idtf::layout::Coords2d {
+            x: zerocopy::byteorder::I16::new(x),
+            y: zerocopy::byteorder::I16::new(y),
+        },
+        status,
+        color,
+    }
+}
+
 #[test]
 fn test_read_all() {
     let test_files_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test_files");
@@ -37,23 +62,1731 @@ fn test_read_all() {
                     _ => panic!("unexpected format layout"),
                 },
             );
-            match section.reader {
-                ilda_idtf::SubsectionReaderKind::Coords3dIndexedColor(mut r) => {
-                    while let Some(_t) = r.read_next().unwrap() {}
+            let format = section.header.format;
+            let is_3d = format == ilda_idtf::layout::Format::COORDS_3D_INDEXED_COLOR
+                || format == ilda_idtf::layout::Format::COORDS_3D_TRUE_COLOR;
+            let palette = section.palette;
+            let mut num_points = 0;
+            section
+                .reader
+                .for_each_point(|point| {
+                    assert_eq!(point.z().is_some(), is_3d);
+                    if point.status().is_blanking() {
+                        assert_eq!(point.color(palette), ilda_idtf::layout::Color::BLACK);
+                    }
+                    num_points += 1;
+                })
+                .unwrap();
+            if format != ilda_idtf::layout::Format::COLOR_PALETTE {
+                assert_eq!(num_points, section.header.num_records.get());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_write_read_roundtrip() {
+    use ilda_idtf::layout::{Color, Coords2d, Coords2dTrueColor, Format, Header, Name, Status};
+    use zerocopy::byteorder::I16;
+
+    let points: Vec<Coords2dTrueColor> = (0..4i16)
+        .map(|i| Coords2dTrueColor {
+            coords: Coords2d {
+                x: I16::new(i * 100),
+                y: I16::new(-i * 100),
+            },
+            status: if i == 3 {
+                Status::LAST_POINT
+            } else {
+                Status::empty()
+            },
+            color: Color {
+                red: 255,
+                green: i as u8,
+                blue: 0,
+            },
+        })
+        .collect();
+    let mut header = Header::new(Format::COORDS_2D_TRUE_COLOR, points.len() as u16);
+    header.data_name = Name::new("square");
+    header.company_name = Name::new("nannou");
+
+    let mut writer = ilda_idtf::SectionWriter::new(vec![]);
+    let mut section = writer.begin_section::<Coords2dTrueColor>(&header).unwrap();
+    for point in &points {
+        section.write_next(point).unwrap();
+    }
+    assert!(section.write_next(&points[0]).is_err());
+    section.finish().unwrap();
+    writer.write_section(&header, &points).unwrap();
+    assert!(writer.write_section(&header, &points[1..]).is_err());
+    let bytes = writer.into_inner();
+
+    let mut short_writer = ilda_idtf::SectionWriter::new(vec![]);
+    let short = short_writer
+        .begin_section::<Coords2dTrueColor>(&header)
+        .unwrap();
+    assert!(short.finish().is_err());
+
+    // Subsections that fail to be written are not counted.
+    let mut buffer = [0u8; 36];
+    let mut full_writer = ilda_idtf::SectionWriter::new(&mut buffer[..]);
+    let mut full = full_writer
+        .begin_section::<Coords2dTrueColor>(&header)
+        .unwrap();
+    assert!(full.write_next(&points[0]).is_err());
+    assert_eq!(full.len(), points.len() as u16);
+
+    let mut reader = ilda_idtf::SectionReader::new(&bytes[..]);
+    for _ in 0..2 {
+        let section = reader.read_next().unwrap().unwrap();
+        assert_eq!(section.header.data_name.as_str(), Ok("square"));
+        assert_eq!(section.header.company_name.as_str(), Ok("nannou"));
+        let mut r = match section.reader {
+            ilda_idtf::SubsectionReaderKind::Coords2dTrueColor(r) => r,
+            _ => panic!("unexpected subsection reader kind"),
+        };
+        let mut read = vec![];
+        while let Some(point) = r.read_next().unwrap() {
+            read.push(*point);
+        }
+        assert_eq!(read, points);
+    }
+    assert!(reader.read_next().unwrap().is_none());
+}
+
+#[test]
+fn test_read_animation() {
+    let test_files_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test_files");
+    let path = test_files_path.join("ILDAsample").join("HIPHOP18.ILD");
+    let animation = ilda_idtf::read_animation(&path).unwrap();
+    assert!(animation.palettes.is_empty());
+    assert_eq!(animation.frames.len(), 18);
+    for (i, frame) in animation.frames.iter().enumerate() {
+        assert_eq!(frame.number as usize, i);
+        assert_eq!(frame.total as usize, animation.frames.len());
+        assert!(!frame.points.is_empty());
+        for point in &frame.points {
+            assert!(point.z.is_some());
+            if point.blanking {
+                assert_eq!(
+                    point.color,
+                    ilda_idtf::layout::Color {
+                        red: 0,
+                        green: 0,
+                        blue: 0
+                    }
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_palette_tracking() {
+    use ilda_idtf::layout::{
+        Color, ColorPalette, Coords2d, Coords2dIndexedColor, Format, Header, Status,
+    };
+    use ilda_idtf::SubsectionReaderKind;
+    use zerocopy::byteorder::I16;
+
+    let colors = [
+        Color {
+            red: 1,
+            green: 2,
+            blue: 3,
+        },
+        Color {
+            red: 4,
+            green: 5,
+            blue: 6,
+        },
+    ];
+    let palette: Vec<_> = colors.iter().map(|&color| ColorPalette { color }).collect();
+    let point = |color_index, status| Coords2dIndexedColor {
+        coords: Coords2d {
+            x: I16::new(0),
+            y: I16::new(0),
+        },
+        status,
+        color_index,
+    };
+    let points = [
+        point(1, Status::empty()),
+        point(1, Status::BLANKING | Status::LAST_POINT),
+    ];
+
+    let mut writer = ilda_idtf::SectionWriter::new(vec![]);
+    let mut palette_header = Header::new(Format::COLOR_PALETTE, palette.len() as u16);
+    palette_header.projector_number = 1;
+    writer.write_section(&palette_header, &palette).unwrap();
+    for &projector in &[0, 1] {
+        let mut header = Header::new(Format::COORDS_2D_INDEXED_COLOR, points.len() as u16);
+        header.projector_number = projector;
+        writer.write_section(&header, &points).unwrap();
+    }
+    let bytes = writer.into_inner();
+
+    let mut reader = ilda_idtf::SectionReader::new(&bytes[..]).palette_tracking(true);
+    let mut resolved = vec![];
+    while let Some(section) = reader.read_next().unwrap() {
+        let palette = section.palette;
+        match section.reader {
+            SubsectionReaderKind::ColorPalette(mut r) => {
+                assert_eq!(palette, &colors[..]);
+                let mut read = vec![];
+                while let Some(p) = r.read_next().unwrap() {
+                    read.push(p.color);
                 }
-                ilda_idtf::SubsectionReaderKind::Coords2dIndexedColor(mut r) => {
-                    while let Some(_t) = r.read_next().unwrap() {}
+                assert_eq!(read, colors);
+            }
+            SubsectionReaderKind::Coords2dIndexedColor(mut r) => {
+                while let Some(p) = r.read_next_resolved(palette).unwrap() {
+                    resolved.push(p.color);
                 }
-                ilda_idtf::SubsectionReaderKind::ColorPalette(mut r) => {
-                    while let Some(_t) = r.read_next().unwrap() {}
+            }
+            _ => panic!("unexpected subsection reader kind"),
+        }
+    }
+    let expected = [
+        ilda_idtf::DEFAULT_PALETTE[1],
+        Color::BLACK,
+        colors[1],
+        Color::BLACK,
+    ];
+    assert_eq!(resolved, expected);
+    assert_eq!(reader.palettes().unwrap().get(1), &colors[..]);
+    assert_eq!(
+        reader.palettes().unwrap().get(0),
+        &ilda_idtf::DEFAULT_PALETTE[..]
+    );
+
+    let mut reader = ilda_idtf::SectionReader::new(&bytes[..]);
+    let animation = ilda_idtf::Animation::read_from(&mut reader).unwrap();
+    assert_eq!(animation.palettes.len(), 1);
+    assert_eq!(
+        animation.frames[0].points[0].color,
+        ilda_idtf::DEFAULT_PALETTE[1]
+    );
+    assert_eq!(animation.frames[1].points[0].color, colors[1]);
+}
+
+#[test]
+fn test_errors() {
+    use ilda_idtf::layout::{Color, Coords2d, Coords2dTrueColor, Format, Header, Status};
+    use ilda_idtf::{Error, Position, SubsectionReaderKind};
+    use zerocopy::{byteorder::I16, AsBytes};
+
+    let header = Header::new(Format::COORDS_2D_TRUE_COLOR, 2);
+    let second = Position {
+        section: 1,
+        offset: 32 + 2 * 8,
+    };
+
+    // A truncated final section.
+    let mut bytes = vec![];
+    bytes.extend(header.as_bytes());
+    bytes.extend(&[0u8; 16]);
+    bytes.extend(header.as_bytes());
+    bytes.extend(&[0u8; 12]);
+    let mut reader = ilda_idtf::SectionReader::new(&bytes[..]);
+    reader.read_next().unwrap().unwrap();
+    assert_eq!(reader.position(), second);
+    let section = reader.read_next().unwrap().unwrap();
+    let mut r = match section.reader {
+        SubsectionReaderKind::Coords2dTrueColor(r) => r,
+        _ => panic!("unexpected subsection reader kind"),
+    };
+    r.read_next().unwrap().unwrap();
+    match r.read_next() {
+        Err(Error::TruncatedSection {
+            position,
+            expected: 2,
+            got: 1,
+        }) => assert_eq!(position.offset, second.offset + 32 + 8),
+        _ => panic!("expected a truncated section"),
+    }
+
+    // An unknown format.
+    let mut bytes = vec![];
+    bytes.extend(header.as_bytes());
+    bytes.extend(&[0u8; 16]);
+    bytes.extend(Header::new(Format(3), 0).as_bytes());
+    let mut reader = ilda_idtf::SectionReader::new(&bytes[..]);
+    reader.read_next().unwrap().unwrap();
+    match reader.read_next() {
+        Err(Error::UnknownFormat {
+            position,
+            format: 3,
+        }) => assert_eq!(position, second),
+        _ => panic!("expected an unknown format"),
+    }
+
+    // Invalid ILDA ascii due to a misreported number of records.
+    let mut bytes = vec![];
+    bytes.extend(Header::new(Format::COORDS_2D_TRUE_COLOR, 1).as_bytes());
+    bytes.extend(&[0u8; 16]);
+    bytes.extend(header.as_bytes());
+    let mut reader = ilda_idtf::SectionReader::new(&bytes[..]);
+    reader.read_next().unwrap().unwrap();
+    let err = match reader.read_next() {
+        Err(err @ Error::BadMagic { .. }) => err,
+        _ => panic!("expected bad magic"),
+    };
+    let expected = Position {
+        section: 1,
+        offset: 32 + 8,
+    };
+    assert_eq!(err.position(), Some(expected));
+    let io_err: std::io::Error = err.into();
+    assert_eq!(io_err.kind(), std::io::ErrorKind::InvalidData);
+
+    // Mismatched writer record counts.
+    let mut writer = ilda_idtf::SectionWriter::new(vec![]);
+    let point = Coords2dTrueColor {
+        coords: Coords2d {
+            x: I16::new(0),
+            y: I16::new(0),
+        },
+        status: Status::empty(),
+        color: Color::BLACK,
+    };
+    let points = [point; 3];
+    match writer.write_section(&header, &points) {
+        Err(Error::ExcessRecords { expected: 2, .. }) => (),
+        _ => panic!("expected excess records"),
+    }
+    match writer.write_section(&header, &points[..1]) {
+        Err(Error::TruncatedSection {
+            expected: 2,
+            got: 1,
+            ..
+        }) => (),
+        _ => panic!("expected a truncated section"),
+    }
+    match writer.write_section::<Coords2dTrueColor>(&Header::new(Format::COLOR_PALETTE, 0), &[]) {
+        Err(Error::UnexpectedFormat { .. }) => (),
+        _ => panic!("expected an unexpected format"),
+    }
+
+    // Only finishing the writer may produce an end of file header.
+    let empty = Header::new(Format::COORDS_2D_TRUE_COLOR, 0);
+    match writer.write_section::<Coords2dTrueColor>(&empty, &[]) {
+        Err(Error::EmptySection { .. }) => (),
+        _ => panic!("expected an empty section"),
+    }
+    match writer.begin_section::<Coords2dTrueColor>(&empty) {
+        Err(Error::EmptySection { .. }) => (),
+        _ => panic!("expected an empty section"),
+    }
+    assert!(writer.into_inner().is_empty());
+}
+
+#[test]
+fn test_strict() {
+    use ilda_idtf::layout::{Format, Header};
+    use ilda_idtf::Error;
+    use zerocopy::AsBytes;
+
+    // Files with trailing bytes are reported as truncated headers.
+    let test_files_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test_files");
+    let path = test_files_path.join("shownet/ILDA_Files_ShowNET_SD-Card_1_0/059.ild");
+    let mut reader = ilda_idtf::open(&path).unwrap().strict(true);
+    let err = loop {
+        match reader.read_next() {
+            Ok(Some(_)) => continue,
+            Ok(None) => panic!("expected a truncated header"),
+            Err(err) => break err,
+        }
+    };
+    match err {
+        Error::TruncatedHeader { got: 1, .. } => (),
+        err => panic!("expected a truncated header, found {}", err),
+    }
+
+    // Otherwise the truncated header ends the stream and is reported as a warning.
+    let mut reader = ilda_idtf::open(&path).unwrap();
+    while reader.read_next().unwrap().is_some() {}
+    match reader.warnings() {
+        [ilda_idtf::Warning::TruncatedHeader { got: 1, .. }] => (),
+        warnings => panic!("unexpected warnings {:?}", warnings),
+    }
+
+    // Reserved fields and palette sizes are verified.
+    let mut header = Header::new(Format::COORDS_2D_TRUE_COLOR, 1);
+    header.reserved2 = 1;
+    let mut reader = ilda_idtf::SectionReader::new(header.as_bytes()).strict(true);
+    match reader.read_next() {
+        Err(Error::NonZeroReserved { .. }) => (),
+        _ => panic!("expected non-zero reserved"),
+    }
+    let header = Header::new(Format::COLOR_PALETTE, 1);
+    let mut reader = ilda_idtf::SectionReader::new(header.as_bytes()).strict(true);
+    match reader.read_next() {
+        Err(Error::InvalidPaletteSize { len: 1, .. }) => (),
+        _ => panic!("expected invalid palette size"),
+    }
+
+    // I/O errors are propagated rather than treated as the end of the stream.
+    struct Failing;
+    impl std::io::Read for Failing {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("failing"))
+        }
+    }
+    let mut reader = ilda_idtf::SectionReader::new(Failing);
+    match reader.read_next() {
+        Err(Error::Io(_)) => (),
+        _ => panic!("expected an I/O error"),
+    };
+}
+
+#[test]
+fn test_end_of_file() {
+    use ilda_idtf::layout::{Color, ColorPalette, Format, Header, Name};
+
+    let mut header = Header::new(Format::COLOR_PALETTE, 2);
+    header.data_name = Name::new("palette");
+    header.company_name = Name::new("nannou");
+    let palette = [ColorPalette {
+        color: Color::BLACK,
+    }; 2];
+    let mut writer = ilda_idtf::SectionWriter::new(vec![]);
+    writer.write_section(&header, &palette).unwrap();
+    let mut bytes = writer.finish().unwrap();
+    let len = bytes.len();
+    assert_eq!(len, 2 * 32 + 2 * 3);
+
+    // The end of file header terminates the stream, even when followed by more bytes.
+    bytes.extend(&[0xffu8; 7]);
+    let mut reader = ilda_idtf::SectionReader::new(&bytes[..]);
+    assert!(reader.read_next().unwrap().is_some());
+    assert!(reader.end_of_file().is_none());
+    assert!(reader.read_next().unwrap().is_none());
+    assert!(reader.read_next().unwrap().is_none());
+    let end = reader.end_of_file().unwrap();
+    assert_eq!(end.num_records.get(), 0);
+    assert_eq!(end.data_name.as_str(), Ok("palette"));
+    assert_eq!(end.company_name.as_str(), Ok("nannou"));
+
+    // Optionally yield the end of file header as an empty section.
+    let mut reader = ilda_idtf::SectionReader::new(&bytes[..len]).stop_at_end_of_file(false);
+    assert!(reader.read_next().unwrap().is_some());
+    {
+        let section = reader.read_next().unwrap().unwrap();
+        assert_eq!(section.header.num_records.get(), 0);
+    }
+    assert!(reader.read_next().unwrap().is_none());
+    assert!(reader.end_of_file().is_none());
+
+    // Streams without an end of file header simply end.
+    let mut reader = ilda_idtf::SectionReader::new(&bytes[..len - 32]);
+    assert!(reader.read_next().unwrap().is_some());
+    assert!(reader.read_next().unwrap().is_none());
+    assert!(reader.end_of_file().is_none());
+}
+
+#[test]
+fn test_slice_reader() {
+    let test_files_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test_files");
+    for entry in walkdir::WalkDir::new(test_files_path) {
+        let entry = entry.unwrap();
+        let path = entry.path();
+        let ext = path.extension().and_then(|s| s.to_str());
+        if ext != Some("ild") && ext != Some("ILD") {
+            continue;
+        }
+        let bytes = std::fs::read(path).unwrap();
+        let mut slice_reader = ilda_idtf::SliceSectionReader::new(&bytes);
+        let mut reader = ilda_idtf::SectionReader::new(&bytes[..]);
+        while let Some(section) = reader.read_next().unwrap() {
+            let slice_section = slice_reader.read_next().unwrap().unwrap();
+            assert_eq!(section.header, slice_section.header);
+            let len = slice_section.records.len();
+            assert_eq!(len, section.header.num_records.get() as usize);
+            match (section.reader, slice_section.records) {
+                (
+                    ilda_idtf::SubsectionReaderKind::Coords3dIndexedColor(mut r),
+                    ilda_idtf::SliceRecords::Coords3dIndexedColor(points),
+                ) => {
+                    for point in points {
+                        assert_eq!(r.read_next().unwrap(), Some(point));
+                    }
                 }
-                ilda_idtf::SubsectionReaderKind::Coords3dTrueColor(mut r) => {
-                    while let Some(_t) = r.read_next().unwrap() {}
+                (
+                    ilda_idtf::SubsectionReaderKind::Coords2dTrueColor(mut r),
+                    ilda_idtf::SliceRecords::Coords2dTrueColor(points),
+                ) => {
+                    for point in points {
+                        assert_eq!(r.read_next().unwrap(), Some(point));
+                    }
                 }
-                ilda_idtf::SubsectionReaderKind::Coords2dTrueColor(mut r) => {
-                    while let Some(_t) = r.read_next().unwrap() {}
+                _ => panic!("unexpected section format"),
+            }
+        }
+        assert!(slice_reader.read_next().unwrap().is_none());
+        assert_eq!(reader.end_of_file(), slice_reader.end_of_file());
+        assert_eq!(reader.position(), slice_reader.position());
+    }
+
+    // End of file headers of an unknown format are rejected by both readers.
+    let header = ilda_idtf::layout::Header::new(ilda_idtf::layout::Format(3), 0);
+    let bytes = zerocopy::AsBytes::as_bytes(&header);
+    let mut reader = ilda_idtf::SectionReader::new(bytes);
+    match reader.read_next() {
+        Err(ilda_idtf::Error::UnknownFormat { format: 3, .. }) => (),
+        _ => panic!("expected an unknown format"),
+    }
+    match ilda_idtf::SliceSectionReader::new(bytes).read_next() {
+        Err(ilda_idtf::Error::UnknownFormat { format: 3, .. }) => (),
+        _ => panic!("expected an unknown format"),
+    }
+}
+
+#[cfg(feature = "memmap")]
+#[test]
+fn test_open_mmap() {
+    let test_files_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test_files");
+    let path = test_files_path.join("td").join("Horse.ILD");
+    // The test files are not modified while mapped.
+    let mmap = unsafe { ilda_idtf::open_mmap(&path).unwrap() };
+    let sections = ilda_idtf::SliceSectionReader::new(&mmap)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(sections.len(), 6);
+}
+
+#[test]
+fn test_bulk_read() {
+    use ilda_idtf::SubsectionReaderKind;
+
+    let test_files_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test_files");
+    let path = test_files_path.join("shownet/255.ild");
+    let bytes = std::fs::read(path).unwrap();
+    let mut slice_reader = ilda_idtf::SliceSectionReader::new(&bytes);
+    let expected = match slice_reader.read_next().unwrap().unwrap().records {
+        ilda_idtf::SliceRecords::Coords2dTrueColor(points) => points,
+        _ => panic!("unexpected section format"),
+    };
+
+    // Read the whole section at once.
+    let mut reader = ilda_idtf::SectionReader::new(&bytes[..]);
+    let section = reader.read_next().unwrap().unwrap();
+    let mut points = vec![];
+    match section.reader {
+        SubsectionReaderKind::Coords2dTrueColor(mut r) => {
+            assert_eq!(r.read_all_into(&mut points).unwrap(), expected.len());
+            assert!(r.is_empty());
+            assert!(r.read_next().unwrap().is_none());
+        }
+        _ => panic!("unexpected subsection reader kind"),
+    }
+    assert_eq!(&points[..], expected);
+
+    // Read in chunks, interleaved with single reads.
+    let mut reader = ilda_idtf::SectionReader::new(&bytes[..]);
+    let section = reader.read_next().unwrap().unwrap();
+    let mut points = vec![];
+    match section.reader {
+        SubsectionReaderKind::Coords2dTrueColor(mut r) => {
+            let mut chunk = [expected[0]; 100];
+            points.push(*r.read_next().unwrap().unwrap());
+            loop {
+                let n = r.read_into(&mut chunk).unwrap();
+                if n == 0 {
+                    break;
                 }
+                points.extend(&chunk[..n]);
             }
         }
+        _ => panic!("unexpected subsection reader kind"),
     }
+    assert_eq!(&points[..], expected);
+
+    // Truncated sections are reported with the number of whole records read.
+    let truncated = &bytes[..32 + 10 * 8 + 3];
+    let mut reader = ilda_idtf::SectionReader::new(truncated);
+    let section = reader.read_next().unwrap().unwrap();
+    let mut points = vec![];
+    match section.reader {
+        SubsectionReaderKind::Coords2dTrueColor(mut r) => match r.read_all_into(&mut points) {
+            Err(ilda_idtf::Error::TruncatedSection { got: 10, .. }) => (),
+            _ => panic!("expected a truncated section"),
+        },
+        _ => panic!("unexpected subsection reader kind"),
+    }
+    assert!(points.is_empty());
+}
+
+#[test]
+fn test_section_index() {
+    let test_files_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test_files");
+    let path = test_files_path.join("shownet/ILDA_Files_ShowNET_SD-Card_1_0/019.ild");
+    let bytes = std::fs::read(&path).unwrap();
+    let sections = ilda_idtf::SliceSectionReader::new(&bytes)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    let mut reader = ilda_idtf::open(&path).unwrap();
+    reader.read_next().unwrap().unwrap();
+    let index = reader.index().unwrap();
+    assert_eq!(index.len(), sections.len());
+    for (entry, section) in index.entries().iter().zip(&sections) {
+        assert_eq!(&entry.header, section.header);
+    }
+
+    // Indexing leaves the reader where it was.
+    let header = *reader.read_next().unwrap().unwrap().header;
+    assert_eq!(&header, sections[1].header);
+
+    // Seek directly to a section or frame.
+    let number = sections[1500].header.data_number.get();
+    let projector = sections[1500].header.projector_number;
+    assert!(reader.seek_to_frame(&index, projector, number).unwrap());
+    assert_eq!(reader.position(), index.entries()[1500].position);
+    let header = *reader.read_next().unwrap().unwrap().header;
+    assert_eq!(&header, sections[1500].header);
+    assert!(reader.seek_to_section(&index, 3).unwrap());
+    let header = *reader.read_next().unwrap().unwrap().header;
+    assert_eq!(&header, sections[3].header);
+    assert!(!reader.seek_to_section(&index, sections.len()).unwrap());
+    assert!(!reader.seek_to_frame(&index, 255, 0).unwrap());
+
+    // Reading continues through to the end of file header.
+    assert!(reader.seek_to_section(&index, sections.len() - 1).unwrap());
+    assert!(reader.read_next().unwrap().is_some());
+    assert!(reader.read_next().unwrap().is_none());
+    assert!(reader.end_of_file().is_some());
+}
+
+#[test]
+fn test_open_options() {
+    let test_files_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test_files");
+    assert!(ilda_idtf::open(test_files_path.join("does_not_exist.ild")).is_err());
+    assert!(ilda_idtf::read_animation(test_files_path.join("does_not_exist.ild")).is_err());
+
+    let path = test_files_path.join("td").join("Horse.ILD");
+    let mut reader = ilda_idtf::OpenOptions::new()
+        .buffer_capacity(64)
+        .strict(true)
+        .palette_tracking(true)
+        .open(&path)
+        .unwrap();
+    let mut sections = 0;
+    while let Some(section) = reader.read_next().unwrap() {
+        assert_eq!(section.palette, &ilda_idtf::DEFAULT_PALETTE[..]);
+        sections += 1;
+    }
+    assert_eq!(sections, 6);
+    assert!(reader.palettes().is_some());
+
+    let mut reader = ilda_idtf::OpenOptions::new()
+        .max_records(16)
+        .open(&path)
+        .unwrap();
+    match reader.read_next() {
+        Err(ilda_idtf::Error::RecordLimitExceeded { max: 16, .. }) => (),
+        _ => panic!("expected the record limit to be exceeded"),
+    };
+}
+
+#[test]
+fn test_validate() {
+    use ilda_idtf::layout::{Coords2dTrueColor, Format, Header, Name, Status};
+    use ilda_idtf::validate::{NameField, ViolationKind};
+    use zerocopy::AsBytes;
+
+    let test_files_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test_files");
+    let report = ilda_idtf::validate::validate_path(test_files_path.join("td/Horse.ILD")).unwrap();
+    assert_eq!(report.sections, 6);
+    assert!(report.is_conforming());
+
+    // Construct a stream violating many aspects of the specification.
+    let mut bytes = vec![];
+    let mut palette = Header::new(Format::COLOR_PALETTE, 1);
+    palette.color_or_total_frames.set(2);
+    palette.reserved2 = 1;
+    bytes.extend(palette.as_bytes());
+    bytes.extend(ilda_idtf::DEFAULT_PALETTE[0].as_bytes());
+    let point = |status| Coords2dTrueColor {
+        coords: ilda_idtf::layout::Coords2d {
+            x: zerocopy::byteorder::I16::new(0),
+            y: zerocopy::byteorder::I16::new(0),
+        },
+        status,
+        color: ilda_idtf::layout::Color::BLACK,
+    };
+    for (number, total) in [(0, 2), (1, 3)].iter() {
+        let mut frame = Header::new(Format::COORDS_2D_TRUE_COLOR, 2);
+        frame.data_number.set(*number);
+        frame.color_or_total_frames.set(*total);
+        frame.data_name = Name::new("Frame");
+        frame.company_name.0[0] = 0xFF;
+        bytes.extend(frame.as_bytes());
+        let mut first = point(Status::LAST_POINT).as_bytes().to_vec();
+        first[4] |= 0b0000_0001; // An undefined status bit.
+        bytes.extend(first);
+        bytes.extend(point(Status::empty()).as_bytes());
+    }
+
+    let report = ilda_idtf::validate::validate(&bytes[..]).unwrap();
+    assert_eq!(report.sections, 3);
+    let kinds: Vec<_> = report.violations.iter().map(|v| v.kind).collect();
+    let point_violations = |kinds: &mut Vec<_>| {
+        kinds.push(ViolationKind::UndefinedStatusBits {
+            index: 0,
+            bits: 0b0000_0001,
+        });
+        kinds.push(ViolationKind::EarlyLastPoint { index: 0 });
+        kinds.push(ViolationKind::MissingLastPoint);
+    };
+    let mut expected = vec![
+        ViolationKind::NonZeroReserved,
+        ViolationKind::InvalidPaletteSize { len: 1 },
+        ViolationKind::NonZeroPaletteTotal { total: 2 },
+    ];
+    let name = ViolationKind::NonAsciiName {
+        field: NameField::CompanyName,
+    };
+    expected.push(name);
+    point_violations(&mut expected);
+    expected.push(name);
+    expected.push(ViolationKind::TotalFramesMismatch {
+        expected: 2,
+        found: 3,
+    });
+    point_violations(&mut expected);
+    expected.push(ViolationKind::MissingEndOfFile);
+    assert_eq!(kinds, expected);
+
+    // Point violations refer to the offending record.
+    let last = &report.violations[report.violations.len() - 2];
+    assert_eq!(last.position.section, 2);
+    assert_eq!(last.position.offset, bytes.len() as u64 - 8);
+    assert_eq!(
+        report.violations[report.violations.len() - 1]
+            .position
+            .offset,
+        bytes.len() as u64
+    );
+}
+
+#[test]
+fn test_recover() {
+    use ilda_idtf::{Error, Warning};
+
+    // Understate the number of records in the first header so that the reader misaligns.
+    let test_files_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test_files");
+    let mut bytes = std::fs::read(test_files_path.join("td/Horse.ILD")).unwrap();
+    let len = u16::from_be_bytes([bytes[24], bytes[25]]);
+    bytes[24..26].copy_from_slice(&(len - 2).to_be_bytes());
+
+    let mut reader = ilda_idtf::SectionReader::new(&bytes[..]);
+    assert!(reader.read_next().unwrap().is_some());
+    match reader.read_next() {
+        Err(Error::BadMagic { .. }) => (),
+        _ => panic!("expected bad magic"),
+    };
+
+    // In recovery mode the skipped records are reported and all sections are read.
+    let mut reader = ilda_idtf::SectionReader::new(&bytes[..]).recover(true);
+    let mut sections = 0;
+    while reader.read_next().unwrap().is_some() {
+        sections += 1;
+    }
+    assert_eq!(sections, 6);
+    assert!(reader.end_of_file().is_some());
+    let offset = 32 + (len as u64 - 2) * 8;
+    assert_eq!(
+        reader.warnings(),
+        &[Warning::SkippedBytes {
+            position: ilda_idtf::Position { section: 1, offset },
+            len: 16,
+        }]
+    );
+
+    // Trailing garbage without a valid header is skipped entirely.
+    let end = bytes.len() - 32;
+    bytes.truncate(end);
+    bytes.extend(&[0xFF; 37]);
+    bytes.extend(b"ILD");
+    let mut reader = ilda_idtf::SectionReader::new(&bytes[..]).recover(true);
+    while reader.read_next().unwrap().is_some() {}
+    assert!(reader.end_of_file().is_none());
+    match reader.warnings() {
+        [_, Warning::SkippedBytes { position, len: 40 }] => {
+            assert_eq!(position.offset, end as u64);
+        }
+        warnings => panic!("unexpected warnings {:?}", warnings),
+    }
+}
+
+#[test]
+fn test_convert_color() {
+    use ilda_idtf::convert;
+    use ilda_idtf::layout::{Color, Coords3dIndexedColor};
+
+    // Indexed -> true -> indexed via the default palette preserves unblanked color indices.
+    let test_files_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test_files");
+    let mut reader = ilda_idtf::open(test_files_path.join("td/Horse.ILD")).unwrap();
+    let mut frames = 0;
+    while let Some(section) = reader.read_next().unwrap() {
+        let mut points: Vec<Coords3dIndexedColor> = vec![];
+        match section.reader {
+            ilda_idtf::SubsectionReaderKind::Coords3dIndexedColor(mut r) => {
+                r.read_all_into(&mut points).unwrap();
+            }
+            _ => panic!("unexpected format"),
+        }
+        let palette = &ilda_idtf::DEFAULT_PALETTE[..];
+        let true_color = convert::to_true_color(&points, palette);
+        let indexed = convert::to_indexed_color(&true_color, palette);
+        for ((a, b), c) in points.iter().zip(&indexed).zip(&true_color) {
+            assert_eq!(a.coords, b.coords);
+            assert_eq!(a.status, b.status);
+            if !a.status.is_blanking() && (a.color_index as usize) < palette.len() {
+                assert_eq!(a.color_index, b.color_index);
+                assert_eq!(c.color, palette[a.color_index as usize]);
+            }
+        }
+        frames += 1;
+    }
+    assert_eq!(frames, 6);
+
+    // Palettes contain exactly the given colors where possible.
+    let red = Color {
+        red: 255,
+        green: 0,
+        blue: 0,
+    };
+    let palette = convert::generate_palette(vec![red, red], 256);
+    assert_eq!(palette, vec![red, Color::BLACK]);
+    assert_eq!(convert::nearest_color_index(&palette, red), 0);
+    assert_eq!(convert::palette_records(&palette).len(), 2);
+
+    // Otherwise colors are reduced to the maximum palette size.
+    let colors: Vec<_> = (0..=255u8)
+        .flat_map(|r| (0..4u8).map(move |g| (r, g)))
+        .map(|(r, g)| Color {
+            red: r,
+            green: g * 85,
+            blue: 255 - r,
+        })
+        .collect();
+    let palette = convert::generate_palette(colors.iter().cloned(), 64);
+    assert_eq!(palette.len(), 64);
+    for &color in &colors {
+        let nearest = palette[convert::nearest_color_index(&palette, color) as usize];
+        let d = |a: u8, b: u8| (a as i32 - b as i32).abs();
+        assert!(d(color.red, nearest.red) <= 32);
+        assert!(d(color.green, nearest.green) <= 64);
+        assert!(d(color.blue, nearest.blue) <= 32);
+    }
+}
+
+#[test]
+fn test_convert_dimensions() {
+    use ilda_idtf::convert::{self, Projection};
+    use ilda_idtf::layout::{Color, Coords3d, Coords3dTrueColor, Status};
+    use zerocopy::byteorder::I16;
+
+    let point = |x, y, z| Coords3dTrueColor {
+        coords: Coords3d {
+            x: I16::new(x),
+            y: I16::new(y),
+            z: I16::new(z),
+        },
+        status: Status::empty(),
+        color: Color::BLACK,
+    };
+    let points = vec![
+        point(100, -200, 0),
+        point(100, -200, 500),
+        point(100, 100, -1000),
+    ];
+
+    // Orthographic projection drops z.
+    let flat = convert::to_2d(&points, Projection::Orthographic);
+    for (a, b) in points.iter().zip(&flat) {
+        assert_eq!((a.coords.x, a.coords.y), (b.coords.x, b.coords.y));
+        assert_eq!((a.status, a.color), (b.status, b.color));
+    }
+
+    // Perspective projection scales by distance from the camera.
+    let camera_distance = 1000.0;
+    let perspective = Projection::perspective(camera_distance).unwrap();
+    assert_eq!(perspective, Projection::Perspective { camera_distance });
+    let projected = convert::to_2d(&points, perspective);
+    let xy = |i: usize| (projected[i].coords.x.get(), projected[i].coords.y.get());
+    assert_eq!(xy(0), (100, -200));
+    assert_eq!(xy(1), (200, -400));
+    assert_eq!(xy(2), (50, 50));
+
+    // Points at or behind the camera are moved to the edge and blanked.
+    let behind = convert::to_2d(
+        &[point(1, -1, 1000), point(0, 5, 2000)],
+        Projection::Perspective { camera_distance },
+    );
+    let xy = |i: usize| (behind[i].coords.x.get(), behind[i].coords.y.get());
+    assert_eq!(xy(0), (i16::MAX, -i16::MAX));
+    assert_eq!(xy(1), (0, i16::MAX));
+    assert!(behind.iter().all(|p| p.status.is_blanking()));
+    assert!(!projected.iter().any(|p| p.status.is_blanking()));
+
+    // Camera distances that are not positive are rejected, or place all points behind the camera.
+    assert_eq!(Projection::perspective(0.0), None);
+    assert_eq!(Projection::perspective(-5.0), None);
+    assert_eq!(Projection::perspective(f32::NAN), None);
+    let camera_distance = -5.0;
+    let invalid = convert::to_2d(&points, Projection::Perspective { camera_distance });
+    assert!(invalid.iter().all(|p| p.status.is_blanking()));
+    assert_eq!(
+        (invalid[2].coords.x.get(), invalid[2].coords.y.get()),
+        (i16::MAX, i16::MAX)
+    );
+
+    // Promoting to 3D places points at z = 0.
+    let promoted = convert::to_3d(&flat);
+    for (a, b) in points.iter().zip(&promoted) {
+        assert_eq!((a.coords.x, a.coords.y), (b.coords.x, b.coords.y));
+        assert_eq!(b.coords.z.get(), 0);
+    }
+}
+
+#[test]
+fn test_transform() {
+    use ilda_idtf::layout::Status;
+    use ilda_idtf::layout::{Color, Coords2d, Coords2dTrueColor, Coords3d, Coords3dTrueColor};
+    use ilda_idtf::transform::{self, Affine2, Affine3, Homography, Overflow, Transform2};
+    use zerocopy::byteorder::I16;
+
+    let point = |x, y| Coords2dTrueColor {
+        coords: Coords2d {
+            x: I16::new(x),
+            y: I16::new(y),
+        },
+        status: Status::empty(),
+        color: Color::BLACK,
+    };
+    let xy = |p: &Coords2dTrueColor| (p.coords.x.get(), p.coords.y.get());
+
+    // Rotate a quarter turn, mirror and then translate.
+    let affine = Affine2::rotation(std::f32::consts::FRAC_PI_2)
+        .then(&Affine2::mirror_x())
+        .then(&Affine2::translation(10.0, -10.0));
+    let mut points = vec![point(100, 0), point(0, 200)];
+    transform::apply_2d(&mut points, &affine, Overflow::Error).unwrap();
+    assert_eq!(xy(&points[0]), (10, 90));
+    assert_eq!(xy(&points[1]), (210, -10));
+
+    // Coordinates beyond the range of `i16` are either reported or clamped, never wrapped.
+    let scale = Affine2::scale(4.0, -4.0);
+    let mut points = vec![point(100, 100), point(10_000, -10_000)];
+    let err = transform::apply_2d(&mut points, &scale, Overflow::Error).unwrap_err();
+    assert_eq!(err.index, 1);
+    assert_eq!(xy(&points[0]), (100, 100));
+    transform::apply_2d(&mut points, &scale, Overflow::Clamp).unwrap();
+    assert_eq!(xy(&points[0]), (400, -400));
+    assert_eq!(xy(&points[1]), (i16::MAX, i16::MAX));
+
+    // 3D transforms.
+    let mut points = vec![Coords3dTrueColor {
+        coords: Coords3d {
+            x: I16::new(0),
+            y: I16::new(100),
+            z: I16::new(0),
+        },
+        status: Status::empty(),
+        color: Color::BLACK,
+    }];
+    let affine = Affine3::rotation_x(std::f32::consts::FRAC_PI_2)
+        .then(&Affine3::scale(1.0, 1.0, 2.0))
+        .then(&Affine3::translation(5.0, 0.0, 0.0));
+    transform::apply_3d(&mut points, &affine, Overflow::Error).unwrap();
+    let c = points[0].coords;
+    assert_eq!((c.x.get(), c.y.get(), c.z.get()), (5, 0, 200));
+
+    // Keystone correction maps the corners of the coordinate range onto the given corners.
+    let corners = [
+        [-20000.0, -30000.0],
+        [20000.0, -30000.0],
+        [30000.0, 30000.0],
+        [-30000.0, 30000.0],
+    ];
+    let keystone = Homography::keystone(corners).unwrap();
+    let max = i16::MAX as f32;
+    let sources = [[-max, -max], [max, -max], [max, max], [-max, max]];
+    for (source, corner) in sources.iter().zip(&corners) {
+        let [x, y] = keystone.transform(*source);
+        assert!((x - corner[0]).abs() < 1.0 && (y - corner[1]).abs() < 1.0);
+    }
+    let [x, y] = keystone.transform([0.0, 0.0]);
+    assert!(x.abs() < 1.0 && y < 0.0);
+    assert!(Homography::keystone([[0.0, 0.0], [1.0, 1.0], [2.0, 2.0], [3.0, 3.0]]).is_none());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    use ilda_idtf::layout::{Coords3dIndexedColor, Format, Header, Name, Status};
+
+    let test_files_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test_files");
+    let mut reader = ilda_idtf::open(test_files_path.join("td/Horse.ILD")).unwrap();
+    let section = reader.read_next().unwrap().unwrap();
+    let header = *section.header;
+    let mut points: Vec<Coords3dIndexedColor> = vec![];
+    match section.reader {
+        ilda_idtf::SubsectionReaderKind::Coords3dIndexedColor(mut r) => {
+            r.read_all_into(&mut points).unwrap();
+        }
+        _ => panic!("unexpected format"),
+    }
+
+    // Layout types round-trip.
+    let json = serde_json::to_string(&header).unwrap();
+    assert_eq!(serde_json::from_str::<Header>(&json).unwrap(), header);
+    let json = serde_json::to_string(&points).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Vec<Coords3dIndexedColor>>(&json).unwrap(),
+        points
+    );
+
+    // Fields take their human-friendly forms.
+    let mut header = Header::new(Format::COORDS_2D_TRUE_COLOR, 3);
+    header.data_name = Name::new("Frame");
+    header.data_number.set(1);
+    let value = serde_json::to_value(header).unwrap();
+    let expected = serde_json::json!({
+        "format": "Coords2dTrueColor",
+        "data_name": "Frame",
+        "company_name": "",
+        "num_records": 3,
+        "data_number": 1,
+        "color_or_total_frames": 0,
+        "projector_number": 0,
+    });
+    assert_eq!(value, expected);
+    let status = serde_json::to_value(Status::BLANKING).unwrap();
+    assert_eq!(
+        status,
+        serde_json::json!({ "last_point": false, "blanking": true })
+    );
+
+    // Unknown format codes and undefined status bits are retained.
+    let format: Format = serde_json::from_str("3").unwrap();
+    assert_eq!(format, Format(3));
+    assert_eq!(serde_json::to_string(&format).unwrap(), "3");
+    let json = r#"{ "last_point": true, "blanking": false, "undefined": 1 }"#;
+    let status: Status = serde_json::from_str(json).unwrap();
+    assert_eq!(status.bits(), 0b1000_0001);
+    assert!(serde_json::from_str::<Name>(r#""TooLongName""#).is_err());
+
+    // The owned model round-trips too.
+    let animation = ilda_idtf::read_animation(test_files_path.join("td/Horse.ILD")).unwrap();
+    let json = serde_json::to_string(&animation).unwrap();
+    let parsed: ilda_idtf::Animation = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, animation);
+}
+
+#[test]
+fn test_text_round_trip() {
+    use ilda_idtf::text;
+
+    // Test files round-trip, excluding any trailing partial header.
+    let test_files_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test_files");
+    let shownet = test_files_path.join("shownet/ILDA_Files_ShowNET_SD-Card_1_0");
+    let mut paths: Vec<_> = ["ILDAsample", "PeaceDoveAnim", "td"]
+        .iter()
+        .flat_map(|dir| walkdir::WalkDir::new(test_files_path.join(dir)))
+        .map(|entry| entry.unwrap().into_path())
+        .filter(|path| {
+            let ext = path.extension().and_then(|s| s.to_str());
+            ext == Some("ild") || ext == Some("ILD")
+        })
+        .collect();
+    paths.push(shownet.join("002.ild"));
+    paths.push(shownet.join("059.ild"));
+    for path in paths {
+        let bytes = std::fs::read(&path).unwrap();
+        let text = text::to_text(&bytes[..]).unwrap();
+        let parsed = text::from_text(&text).unwrap();
+        assert!(bytes.starts_with(&parsed), "{}", path.display());
+        assert!(bytes.len() - parsed.len() < 32);
+    }
+
+    // Unusual fields are retained.
+    let text = r#"
+        // A comment.
+        section Coords2dTrueColor name="a \"b\"\x01" company="\xff" number=2 total=3 projector=4 reserved=0,1,2 reserved2=5
+          -100 200 - #ff0000
+          100 -200 blank|last|0x01 #00ff80
+        section ColorPalette name="" company="" number=0 total=0 projector=0
+          #000000
+          #ffffff
+        end Coords2dTrueColor name="" company="" number=9 total=0 projector=0
+    "#;
+    let bytes = text::from_text(text).unwrap();
+    assert_eq!(bytes.len(), 32 + 2 * 8 + 32 + 2 * 3 + 32);
+    let canonical = text::to_text(&bytes[..]).unwrap();
+    assert_eq!(text::from_text(&canonical).unwrap(), bytes);
+    assert!(canonical.contains(r#"name="a \"b\"\x01" company="\xff""#));
+    assert!(canonical.contains("reserved=0,1,2 reserved2=5"));
+    assert!(canonical.contains("100 -200 blank|last|0x01 #00ff80"));
+    let end = "end Coords2dTrueColor name=\"\" company=\"\" number=9 total=0 projector=0\n";
+    assert!(canonical.ends_with(end));
+
+    // Errors report the offending line.
+    let text =
+        "section Coords2dTrueColor name=\"\" company=\"\" number=0 total=1 projector=0\n  1 2 -\n";
+    let err = text::from_text(text).unwrap_err();
+    assert_eq!(err.line, 2);
+    let err = text::from_text("section Foo").unwrap_err();
+    assert_eq!(err.line, 1);
+}
+
+#[test]
+fn test_svg() {
+    use ilda_idtf::animation::{Frame, Point};
+    use ilda_idtf::layout::Color;
+
+    let red = Color {
+        red: 255,
+        green: 0,
+        blue: 0,
+    };
+    let green = Color {
+        red: 0,
+        green: 255,
+        blue: 0,
+    };
+    let point = |x, y, color, blanking| Point {
+        x,
+        y,
+        z: None,
+        color,
+        blanking,
+    };
+    let frame = Frame {
+        name: "Frame".to_string(),
+        company: String::new(),
+        number: 0,
+        total: 1,
+        projector: 0,
+        points: vec![
+            point(0, 0, red, false),
+            point(100, 100, red, false),
+            point(200, 0, green, false),
+            point(300, 300, Color::BLACK, true),
+            point(400, -400, red, false),
+        ],
+    };
+    let svg = ilda_idtf::svg::SvgOptions::new()
+        .size(256)
+        .background(None)
+        .frame(&frame);
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.contains("width=\"256\""));
+    assert!(!svg.contains("<rect"));
+    let polylines: Vec<&str> = svg.lines().filter(|l| l.starts_with("<polyline")).collect();
+    assert_eq!(
+        polylines,
+        vec![
+            "<polyline stroke=\"#ff0000\" points=\"0,0 0,0 100,-100\"/>",
+            "<polyline stroke=\"#00ff00\" points=\"100,-100 200,0\"/>",
+            "<polyline stroke=\"#ff0000\" points=\"300,-300 400,400\"/>",
+        ]
+    );
+
+    // Frames read from files may be exported directly from their layout.
+    let test_files_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test_files");
+    let mut reader = ilda_idtf::open(test_files_path.join("td/Horse.ILD")).unwrap();
+    let section = reader.read_next().unwrap().unwrap();
+    let palette = section.palette;
+    let mut points = vec![];
+    if let ilda_idtf::SubsectionReaderKind::Coords3dIndexedColor(mut r) = section.reader {
+        r.read_all_into(&mut points).unwrap();
+    }
+    let svg = ilda_idtf::svg::SvgOptions::new().points(&points, palette);
+    assert!(svg.contains("<rect"));
+    assert!(svg.contains("<polyline"));
+    assert!(svg.ends_with("</svg>\n"));
+}
+
+#[test]
+fn test_svg_import() {
+    use ilda_idtf::layout::{Color, Status};
+
+    let svg = r##"<?xml version="1.0"?>
+<!-- An artist's drawing. -->
+<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+  <path stroke="#ff0000" d="M10,10 H90 q0,80 -40,80 A40 40 0 0 1 10 50z"/>
+  <g style="stroke: lime">
+    <polyline points="20 20, 30 20 30 30"/>
+    <line x1="60" y1="60" x2="70" y2="70" stroke="#00f"/>
+  </g>
+  <path stroke="none" d="M0 0 L100 100"/>
+  <path d="M50 50 C 60 40, 70 40, 80 50 S 90 60, 100 50"/>
+</svg>"##;
+    let points = ilda_idtf::svg::ImportOptions::new()
+        .point_spacing(1024.0)
+        .blank_points(4)
+        .import(svg)
+        .unwrap();
+    let coords = |i: usize| (points[i].coords.x.get(), points[i].coords.y.get());
+
+    // The view box is centered within the coordinate range with y flipped.
+    assert!(points[0].status.is_blanking());
+    assert_eq!(coords(0), (-26214, 26214));
+    assert_eq!(coords(4), (-26214, 26214));
+    assert!(!points[4].status.is_blanking());
+    assert_eq!(
+        points[4].color,
+        Color {
+            red: 255,
+            green: 0,
+            blue: 0
+        }
+    );
+
+    // Lines are divided evenly by the point spacing.
+    assert_eq!(coords(5), (-25205, 26214));
+    assert!(points[5..57].iter().all(|p| p.coords.y.get() == 26214));
+    assert_eq!(coords(56), (26214, 26214));
+
+    // Each subpath ends where it began and travel moves are blanked.
+    let lit: Vec<usize> = (0..points.len())
+        .filter(|&i| !points[i].status.is_blanking())
+        .collect();
+    let first_travel = lit
+        .iter()
+        .position(|&i| points[i + 1].status.is_blanking())
+        .unwrap();
+    assert_eq!(coords(lit[first_travel]), (-26214, 26214));
+
+    // Stroke colors are inherited from groups, `none` is skipped and white is the default.
+    let green = Color {
+        red: 0,
+        green: 255,
+        blue: 0,
+    };
+    let blue = Color {
+        red: 0,
+        green: 0,
+        blue: 255,
+    };
+    let white = Color {
+        red: 255,
+        green: 255,
+        blue: 255,
+    };
+    let mut colors: Vec<Color> = lit.iter().map(|&i| points[i].color).collect();
+    colors.dedup();
+    assert_eq!(colors[1..], [green, blue, white]);
+    let last = points.len() - 1;
+    assert_eq!(coords(last), (32767, 0));
+    assert!(points[last].status.contains(Status::LAST_POINT));
+    assert!(!points[..last].iter().any(|p| p.status.is_last_point()));
+
+    // Malformed documents are reported.
+    assert!(ilda_idtf::svg::svg_to_frame("<svg><path d=\"M0 0 L1\"/></svg>").is_err());
+    assert!(ilda_idtf::svg::svg_to_frame("<svg><path d=\"0 0\"/></svg>").is_err());
+    assert!(ilda_idtf::svg::svg_to_frame("<svg><path d=\"M0 0\"").is_err());
+    assert!(ilda_idtf::svg::svg_to_frame("<svg><path stroke=\"#é1\" d=\"M0 0\"/></svg>").is_err());
+    assert!(ilda_idtf::svg::svg_to_frame("<svg/>").unwrap().is_empty());
+
+    // Lone moves draw nothing and disjoint segments of the same color are joined by travel.
+    let lone = r#"<svg viewBox="0 0 100 100"><path d="M30 30"/></svg>"#;
+    assert!(ilda_idtf::svg::svg_to_frame(lone).unwrap().is_empty());
+    let disjoint = r#"<svg viewBox="0 0 100 100">
+  <path d="M10 10 L20 10"/>
+  <path d="L50 50"/>
+  <line x1="60" y1="60" x2="70" y2="60"/>
+</svg>"#;
+    let points = ilda_idtf::svg::svg_to_frame(disjoint).unwrap();
+    let paths = (1..points.len())
+        .filter(|&i| points[i - 1].status.is_blanking() && !points[i].status.is_blanking())
+        .count();
+    assert_eq!(paths, 3);
+
+    // Documents requiring too many points are rejected before the points are generated.
+    let long = r#"<svg viewBox="0 0 1 1"><path d="M0 0 L 1e9 0"/></svg>"#;
+    assert!(ilda_idtf::svg::svg_to_frame(long).is_err());
+}
+
+#[test]
+fn test_render() {
+    use ilda_idtf::animation::{Frame, Point};
+    use ilda_idtf::layout::Color;
+    use ilda_idtf::render::RenderOptions;
+
+    let red = Color {
+        red: 255,
+        green: 0,
+        blue: 0,
+    };
+    let green = Color {
+        red: 0,
+        green: 255,
+        blue: 0,
+    };
+    let point = |x, y, color, blanking| Point {
+        x,
+        y,
+        z: None,
+        color,
+        blanking,
+    };
+    let frame = Frame {
+        name: "Frame".to_string(),
+        company: String::new(),
+        number: 0,
+        total: 1,
+        projector: 0,
+        points: vec![
+            point(-16384, 0, red, false),
+            point(16384, 0, red, false),
+            point(16384, 16384, Color::BLACK, true),
+            point(-16384, 16384, Color::BLACK, true),
+            point(-16384, 16384, green, false),
+            point(-16384, 16384, green, false),
+            point(-16384, 16384, green, false),
+        ],
+    };
+
+    // Lit segments are drawn in their color while blanked moves are not drawn.
+    let image = ilda_idtf::render::render_frame(&frame);
+    assert_eq!((image.width, image.height), (512, 512));
+    assert_eq!(image.pixels.len(), 512 * 512 * 4);
+    let [r, g, b, a] = image.pixel(256, 255).unwrap();
+    assert!(r > 127 && g == 0 && b == 0 && a == 255);
+    assert_eq!(image.pixel(384, 192), Some([0, 0, 0, 255]));
+    assert_eq!(image.pixel(256, 250), Some([0, 0, 0, 255]));
+    assert_eq!(image.pixel(512, 0), None);
+
+    // Glow spreads light around lit segments.
+    let glow = RenderOptions::new().glow(Some(4.0)).frame(&frame).unwrap();
+    assert!(glow.pixel(256, 250).unwrap()[0] > 0);
+    assert_eq!(glow.pixel(384, 192), Some([0, 0, 0, 255]));
+
+    // Dwell points are drawn larger than a single point.
+    assert_eq!(image.pixel(129, 128), Some([0, 0, 0, 255]));
+    let dwell = RenderOptions::new().dwell(true).frame(&frame).unwrap();
+    assert!(dwell.pixel(129, 128).unwrap()[1] > 0);
+
+    // Transparent backgrounds take their opacity from the light.
+    let transparent = RenderOptions::new()
+        .size(64, 32)
+        .background(None)
+        .frame(&frame)
+        .unwrap();
+    assert_eq!(transparent.pixels.len(), 64 * 32 * 4);
+    assert_eq!(transparent.pixel(0, 0), Some([0, 0, 0, 0]));
+    assert_eq!(transparent.pixel(32, 15).unwrap()[0], 255);
+
+    // Frames read from files may be rendered directly from their layout.
+    let test_files_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test_files");
+    let mut reader = ilda_idtf::open(test_files_path.join("td/Horse.ILD")).unwrap();
+    let section = reader.read_next().unwrap().unwrap();
+    let palette = section.palette;
+    let mut points = vec![];
+    if let ilda_idtf::SubsectionReaderKind::Coords3dIndexedColor(mut r) = section.reader {
+        r.read_all_into(&mut points).unwrap();
+    }
+    let image = RenderOptions::new()
+        .size(128, 128)
+        .points(&points, palette)
+        .unwrap();
+    assert!(image.pixels.chunks(4).any(|p| p[..3] != [0, 0, 0]));
+
+    // Images too large to render are rejected rather than allocated.
+    let huge = RenderOptions::new().size(65535, 65535).frame(&frame);
+    assert_eq!(huge, Err(ilda_idtf::render::ImageTooLarge));
+
+    // Images may be encoded as PNG.
+    #[cfg(feature = "png")]
+    {
+        let mut bytes = vec![];
+        image.write_png(&mut bytes).unwrap();
+        let decoder = png::Decoder::new(&bytes[..]);
+        let mut reader = decoder.read_info().unwrap();
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).unwrap();
+        assert_eq!((info.width, info.height), (128, 128));
+        assert_eq!(buf[..info.buffer_size()], image.pixels[..]);
+    }
+}
+
+#[test]
+fn test_animation_sequences() {
+    use ilda_idtf::animation::{Animation, Frame};
+
+    let frame = |number, total, projector| Frame {
+        name: String::new(),
+        company: String::new(),
+        number,
+        total,
+        projector,
+        points: vec![],
+    };
+    let animation = Animation {
+        frames: vec![
+            frame(0, 2, 0),
+            frame(0, 3, 1),
+            frame(1, 2, 0),
+            frame(1, 3, 1),
+            frame(0, 2, 0),
+            frame(2, 3, 1),
+            frame(1, 4, 0),
+        ],
+        palettes: vec![],
+    };
+    let sequences: Vec<Vec<(u16, u16, u8)>> = animation
+        .sequences()
+        .iter()
+        .map(|s| s.iter().map(|f| (f.number, f.total, f.projector)).collect())
+        .collect();
+    assert_eq!(
+        sequences,
+        vec![
+            vec![(0, 2, 0), (1, 2, 0)],
+            vec![(0, 3, 1), (1, 3, 1), (2, 3, 1)],
+            vec![(0, 2, 0)],
+            vec![(1, 4, 0)],
+        ]
+    );
+
+    // Each of the sample animations forms a single sequence.
+    let test_files_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test_files");
+    let animation = ilda_idtf::read_animation(test_files_path.join("td/Horse.ILD")).unwrap();
+    let sequences = animation.sequences();
+    assert_eq!(sequences.len(), 1);
+    assert_eq!(sequences[0].len(), 6);
+
+    // Sequences may be exported as animations.
+    #[cfg(feature = "gif")]
+    {
+        let mut bytes = vec![];
+        ilda_idtf::render::RenderOptions::new()
+            .size(64, 48)
+            .frame_rate(25.0)
+            .write_gif(sequences[0].iter().cloned(), &mut bytes)
+            .unwrap();
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(&bytes[..]).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (64, 48));
+        let mut delays = vec![];
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            delays.push(frame.delay);
+        }
+        assert_eq!(delays, vec![4; 6]);
+
+        // Nothing is written without any frames.
+        let mut bytes = vec![];
+        let options = ilda_idtf::render::RenderOptions::new();
+        assert!(options.write_gif(vec![], &mut bytes).is_err());
+        assert!(bytes.is_empty());
+        let huge = ilda_idtf::render::RenderOptions::new()
+            .size(65535, 65535)
+            .write_gif(sequences[0].iter().cloned(), &mut bytes);
+        assert!(huge.is_err());
+        assert!(bytes.is_empty());
+    }
+    #[cfg(feature = "png")]
+    {
+        let mut bytes = vec![];
+        ilda_idtf::render::RenderOptions::new()
+            .size(64, 48)
+            .frame_rate(25.0)
+            .write_apng(sequences[0].iter().cloned(), &mut bytes)
+            .unwrap();
+        let mut reader = png::Decoder::new(&bytes[..]).read_info().unwrap();
+        let control = reader.info().animation_control().unwrap();
+        assert_eq!((control.num_frames, control.num_plays), (6, 0));
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let mut frames = 0;
+        while reader.next_frame(&mut buf).is_ok() {
+            let fc = reader.info().frame_control().unwrap();
+            assert_eq!(fc.delay_num as f32 / fc.delay_den as f32, 1.0 / 25.0);
+            frames += 1;
+        }
+        assert_eq!(frames, 6);
+        let none: Vec<&Frame> = vec![];
+        let options = ilda_idtf::render::RenderOptions::new();
+        assert!(options.write_apng(none, vec![]).is_err());
+    }
+}
+
+#[test]
+fn test_optimize_paths() {
+    use ilda_idtf::layout::{Color, Coords2dTrueColor, Status};
+    use ilda_idtf::optimize;
+
+    let color = |red, green, blue| Color { red, green, blue };
+    let lit = |x, y, c| point_2d(x, y, c, Status::empty());
+    let blank = |x, y| point_2d(x, y, Color::BLACK, Status::BLANKING);
+    let (red, green, blue) = (color(255, 0, 0), color(0, 255, 0), color(0, 0, 255));
+    let mut points = vec![
+        blank(0, 0),
+        lit(1000, 0, red),
+        blank(5000, 5000),
+        blank(20000, 20000),
+        lit(21000, 20000, green),
+        blank(3000, 0),
+        lit(2500, 0, blue),
+        lit(2000, 0, red),
+        lit(21000, 21000, Color::BLACK),
+        blank(21000, 21000),
+        lit(20000, 21000, green),
+    ];
+    points.last_mut().unwrap().status |= Status::LAST_POINT;
+
+    // The lit segments drawn, ignoring their direction.
+    let segments = |points: &[Coords2dTrueColor]| {
+        let xy = |p: &Coords2dTrueColor| (p.coords.x.get(), p.coords.y.get());
+        let mut segments: Vec<_> = (0..points.len())
+            .filter(|&i| !points[i].status.is_blanking())
+            .map(|i| {
+                let (a, b) = (xy(&points[i.saturating_sub(1)]), xy(&points[i]));
+                (a.min(b), a.max(b), points[i].color)
+            })
+            .collect();
+        segments.sort_by_key(|&(a, b, c)| (a, b, c.red, c.green, c.blue));
+        segments
+    };
+    let last_points = |points: &[Coords2dTrueColor]| {
+        let last = points.iter().rposition(|p| p.status.is_last_point());
+        (
+            last,
+            points.iter().filter(|p| p.status.is_last_point()).count(),
+        )
+    };
+
+    let original = optimize::blanked_distance(&points);
+    for &reverse in &[false, true] {
+        let optimized = optimize::optimize_paths(&points, reverse).unwrap();
+        assert!(optimize::blanked_distance(&optimized) < original);
+        assert_eq!(segments(&optimized), segments(&points));
+        assert_eq!(last_points(&optimized), (Some(optimized.len() - 1), 1));
+        // One blanked point begins each of the four paths.
+        let blanked = optimized.iter().filter(|p| p.status.is_blanking()).count();
+        assert_eq!(blanked, 4);
+    }
+
+    // Reversal permits shorter travel.
+    let forward = optimize::optimize_paths(&points, false).unwrap();
+    let reversed = optimize::optimize_paths(&points, true).unwrap();
+    assert!(optimize::blanked_distance(&reversed) < optimize::blanked_distance(&forward));
+
+    // Already optimal frames retain their order.
+    let optimal = optimize::optimize_paths(&reversed, true).unwrap();
+    assert_eq!(optimal, reversed);
+
+    // Frames of the maximum size are optimized in bounded time.
+    let mut seed = 1u32;
+    let mut random = || {
+        seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (seed >> 16) as i16
+    };
+    let points: Vec<_> = (0..u16::MAX / 2)
+        .flat_map(|_| {
+            let (x, y) = (random(), random());
+            vec![blank(x, y), lit(x.saturating_add(100), y, red)]
+        })
+        .collect();
+    for &reverse in &[false, true] {
+        let optimized = optimize::optimize_paths(&points, reverse).unwrap();
+        assert_eq!(optimized.len(), points.len());
+        assert!(
+            optimize::blanked_distance(&optimized) < optimize::blanked_distance(&points) / 10.0
+        );
+    }
+
+    // Frames without lit points only have their `LAST_POINT` status corrected.
+    let mut blanks = vec![blank(0, 0), blank(100, 100)];
+    blanks[0].status |= Status::LAST_POINT;
+    let optimized = optimize::optimize_paths(&blanks, true).unwrap();
+    assert_eq!(last_points(&optimized), (Some(1), 1));
+
+    // A single path is emitted as is.
+    let single = vec![blank(0, 0), lit(100, 0, red), lit(200, 0, red)];
+    let optimized = optimize::optimize_paths(&single, true).unwrap();
+    assert_eq!(segments(&optimized), segments(&single));
+    assert_eq!(last_points(&optimized), (Some(2), 1));
+    assert_eq!(optimized.len(), 3);
+
+    // Frames that would exceed the maximum size are rejected.
+    let full: Vec<_> = (0..u16::MAX).map(|i| lit(i as i16, 0, red)).collect();
+    let err = optimize::optimize_paths(&full, false);
+    assert_eq!(err, Err(optimize::TooManyPoints));
+}
+
+#[test]
+fn test_dwell() {
+    use ilda_idtf::layout::{Color, Status};
+    use ilda_idtf::optimize::{self, DwellOptions};
+
+    let red = Color {
+        red: 255,
+        green: 0,
+        blue: 0,
+    };
+    let lit = |x, y| point_2d(x, y, red, Status::empty());
+    let blank = |x, y| point_2d(x, y, Color::BLACK, Status::BLANKING);
+
+    let points = vec![
+        blank(0, 0),
+        lit(1000, 0),
+        lit(1000, 1000),
+        lit(1000, 2000),
+        lit(1000, 1000),
+        blank(5000, 5000),
+        lit(6000, 5000),
+    ];
+    let dwelled = DwellOptions::new().apply(&points).unwrap();
+    let mut expected = vec![(0, 0, true); 4];
+    // A right angle receives half of the corner points, a straight line none and a reversal all.
+    expected.extend(vec![(1000, 0, false); 3]);
+    expected.push((1000, 1000, false));
+    expected.extend(vec![(1000, 2000, false); 5]);
+    expected.push((1000, 1000, false));
+    expected.extend(vec![(1000, 1000, true); 3]);
+    expected.extend(vec![(5000, 5000, true); 4]);
+    expected.push((6000, 5000, false));
+    expected.extend(vec![(6000, 5000, true); 3]);
+    assert_eq!(summary(&dwelled), expected);
+    assert!(dwelled
+        .iter()
+        .all(|p| p.status.is_blanking() || p.color == red));
+    assert!(dwelled.last().unwrap().status.is_last_point());
+    assert_eq!(
+        dwelled.iter().filter(|p| p.status.is_last_point()).count(),
+        1
+    );
+
+    // A lit first point begins its path from its own position.
+    let dwelled = DwellOptions::new()
+        .blank_before(2)
+        .blank_after(1)
+        .corner_points(0)
+        .apply(&[lit(0, 0), lit(10, 0), lit(10, 10)])
+        .unwrap();
+    assert_eq!(
+        summary(&dwelled),
+        vec![
+            (0, 0, true),
+            (0, 0, true),
+            (0, 0, false),
+            (10, 0, false),
+            (10, 10, false),
+            (10, 10, true),
+        ]
+    );
+
+    // Frames that would exceed the maximum number of points are rejected.
+    let points = vec![blank(0, 0), lit(10, 0)];
+    let dwell = DwellOptions::new()
+        .blank_before(u16::MAX as usize - 5)
+        .apply(&points);
+    assert_eq!(dwell.unwrap().len(), u16::MAX as usize);
+    let err = DwellOptions::new().blank_before(usize::MAX).apply(&points);
+    assert_eq!(err, Err(optimize::TooManyPoints));
+}
+
+#[test]
+fn test_resample_decimate() {
+    use ilda_idtf::layout::{Color, Status};
+    use ilda_idtf::optimize;
+
+    let red = Color {
+        red: 255,
+        green: 0,
+        blue: 0,
+    };
+    let green = Color {
+        red: 0,
+        green: 255,
+        blue: 0,
+    };
+    let lit = |x, y, color| point_2d(x, y, color, Status::empty());
+    let blank = |x, y| point_2d(x, y, Color::BLACK, Status::BLANKING);
+
+    // Lit and blanked moves are subdivided according to their own limits, including the move
+    // back to the first point.
+    let mut points = vec![blank(0, 0), lit(1000, 0, red), blank(1000, 5000)];
+    points[2].status |= Status::LAST_POINT;
+    let resampled = optimize::resample(&points, 300.0, 2000.0).unwrap();
+    assert_eq!(
+        summary(&resampled),
+        vec![
+            (0, 0, true),
+            (250, 0, false),
+            (500, 0, false),
+            (750, 0, false),
+            (1000, 0, false),
+            (1000, 1667, true),
+            (1000, 3333, true),
+            (1000, 5000, true),
+            (667, 3333, true),
+            (333, 1667, true),
+        ]
+    );
+    assert!(resampled[1..5].iter().all(|p| p.color == red));
+    assert_eq!(
+        resampled
+            .iter()
+            .filter(|p| p.status.is_last_point())
+            .count(),
+        1
+    );
+    assert!(resampled[9].status.is_last_point());
+    assert_eq!(optimize::resample(&points, 0.0, -1.0), Ok(points.clone()));
+
+    // Steps are limited to whole units and the frame size is bounded.
+    let line = vec![lit(0, 0, red), lit(10, 0, red)];
+    assert_eq!(optimize::resample(&line, 0.01, 0.01).unwrap().len(), 20);
+    let line = vec![blank(-32768, -32768), blank(32767, 32767)];
+    let err = optimize::resample(&line, 1.0, 1.0);
+    assert_eq!(err, Err(optimize::TooManyPoints));
+
+    // Decimation removes the points inserted by resampling within the frame.
+    assert_eq!(optimize::decimate(&resampled[..8], 1.0), points);
+
+    // Color changes, dwell, reversals and deviations beyond the tolerance are kept.
+    let points = vec![
+        lit(0, 0, red),
+        lit(500, 0, red),
+        lit(1000, 0, green),
+        lit(1000, 0, green),
+        lit(2000, 0, green),
+        lit(3000, 0, green),
+        lit(2500, 0, green),
+        lit(3000, 2, green),
+        lit(3500, 0, green),
+    ];
+    let decimated = optimize::decimate(&points, 1.0);
+    let expected = vec![
+        (0, 0, false),
+        (500, 0, false),
+        (1000, 0, false),
+        (1000, 0, false),
+        (3000, 0, false),
+        (2500, 0, false),
+        (3000, 2, false),
+        (3500, 0, false),
+    ];
+    assert_eq!(summary(&decimated), expected);
+    let decimated = optimize::decimate(&points, 5.0);
+    assert_eq!(
+        summary(&decimated)[5..],
+        [(2500, 0, false), (3500, 0, false)]
+    );
+    assert!(decimated.last().unwrap().status.is_last_point());
+
+    // A long straight line with slight deviations decimates to its ends.
+    let line: Vec<_> = (0..u16::MAX as i32)
+        .map(|i| lit((i - 32767) as i16, (i % 2) as i16, red))
+        .collect();
+    let decimated = optimize::decimate(&line, 2.0);
+    assert_eq!(summary(&decimated), [(-32767, 0, false), (32767, 0, false)]);
 }